@@ -0,0 +1,57 @@
+//! Compares the 32-bit compensation with the default integer compensation.
+//!
+//! Run with `cargo bench --bench compensation`. Hosts multiply and divide 64-bit values in
+//! hardware, so the 32-bit gas resistance is slower there. On targets without 64-bit
+//! arithmetic, such as the Cortex-M0, the compiler emulates it with similar loops.
+
+use bme680::calc::Calc;
+use bme680::CalibData;
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+const ITERATIONS: u32 = 1_000_000;
+
+fn calib() -> CalibData {
+    CalibData {
+        par_t1: 26180,
+        par_t2: 26257,
+        par_t3: 3,
+        range_sw_err: 1,
+        ..Default::default()
+    }
+}
+
+/// Runs `f` for `ITERATIONS` raw values and returns the duration per call
+fn bench<F: FnMut(u32) -> u32>(mut f: F) -> Duration {
+    let start = Instant::now();
+    for i in 0..ITERATIONS {
+        black_box(f(black_box(i)));
+    }
+    start.elapsed() / ITERATIONS
+}
+
+fn main() {
+    let calib = calib();
+
+    let temperature = bench(|i| {
+        Calc::calc_temperature(&calib, i & 0xfffff, Some(1.25))
+            .unwrap()
+            .1 as u32
+    });
+    let temperature_i32 = bench(|i| {
+        Calc::calc_temperature_i32(&calib, i & 0xfffff, 125)
+            .unwrap()
+            .1 as u32
+    });
+    println!("calc_temperature:           {:?}", temperature);
+    println!("calc_temperature_i32:       {:?}", temperature_i32);
+
+    let gas = bench(|i| {
+        Calc::calc_gas_resistance(&calib, (i & 0x3ff) as u16, (i >> 10 & 0xf) as u8).unwrap()
+    });
+    let gas_i32 = bench(|i| {
+        Calc::calc_gas_resistance_i32(&calib, (i & 0x3ff) as u16, (i >> 10 & 0xf) as u8).unwrap()
+    });
+    println!("calc_gas_resistance:        {:?}", gas);
+    println!("calc_gas_resistance_i32:    {:?}", gas_i32);
+}
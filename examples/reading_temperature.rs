@@ -9,9 +9,10 @@ use linux_embedded_hal as hal;
 use linux_embedded_hal::Delay;
 use log::info;
 
-fn main(
-) -> result::Result<(), Error<<hal::I2cdev as i2c::Read>::Error, <hal::I2cdev as i2c::Write>::Error>>
-{
+fn main() -> result::Result<
+    (),
+    Error<<hal::I2cdev as i2c::WriteRead>::Error, <hal::I2cdev as i2c::Write>::Error>,
+> {
     env_logger::init();
 
     let i2c = hal::I2cdev::new("/dev/i2c-1").unwrap();
@@ -0,0 +1,637 @@
+//! Async driver built on the embedded-hal-async traits.
+//!
+//! [`Bme680Async`] mirrors the blocking [`Bme680`](crate::Bme680) driver, but every bus
+//! access and delay is awaited, so waiting for a measurement never blocks the executor.
+//!
+//! ```no_run
+//! use bme680::{Bme680Async, I2CAddress, PowerMode, SettingsBuilder};
+//! use core::time::Duration;
+//! use embedded_hal_async::delay::DelayNs;
+//! # use embedded_hal_async::i2c::{ErrorType, I2c, Operation};
+//! # struct I2cdev;
+//! # impl ErrorType for I2cdev {
+//! #     type Error = core::convert::Infallible;
+//! # }
+//! # impl I2c for I2cdev {
+//! #     async fn transaction(&mut self, _addr: u8, _ops: &mut [Operation<'_>]) -> Result<(), Self::Error> {
+//! #         Ok(())
+//! #     }
+//! # }
+//! # struct Delay;
+//! # impl DelayNs for Delay {
+//! #     async fn delay_ns(&mut self, _ns: u32) {}
+//! # }
+//!
+//! async fn measure() -> Result<(), bme680::Error<core::convert::Infallible, core::convert::Infallible>> {
+//!     let mut delayer = Delay;
+//!     let mut dev = Bme680Async::init(I2cdev, &mut delayer, I2CAddress::Primary).await?;
+//!     let settings = SettingsBuilder::new()
+//!         .with_gas_measurement(Duration::from_millis(1500), 320, 25)
+//!         .with_run_gas(true)
+//!         .build();
+//!     dev.set_sensor_settings(&mut delayer, settings).await?;
+//!     let profile_duration = dev.get_profile_dur(&settings.0)?;
+//!
+//!     dev.set_sensor_mode(&mut delayer, PowerMode::ForcedMode).await?;
+//!     delayer.delay_ms(profile_duration.as_millis() as u32).await;
+//!     let (data, _state) = dev.get_sensor_data(&mut delayer).await?;
+//!     Ok(())
+//! }
+//! ```
+
+use crate::interface::burst;
+use crate::{
+    calib_data_from_regs, field_data_from_raw, field_data_set_from_regs, gas_config_from_regs,
+    heater_profile_regs, heater_step_select_reg, parallel_profile_regs, profile_dur,
+    raw_field_data_from_regs, sensor_settings_from_regs, sensor_settings_regs,
+    sequential_profile_regs, validate_calib, CalibData, ChipVariant, Compensation,
+    DesiredSensorSettings, Error, FieldData, FieldDataCondition, FieldDataSet, GasSett, HeaterStep,
+    I2CAddress, ParallelHeaterStep, PowerMode, RawFieldData, Result, SensorSettings, Settings,
+    Snapshot, SnapshotError, TphSett, BME680_ADDR_GAS_CONF_START, BME680_ADDR_RANGE_SW_ERR_ADDR,
+    BME680_ADDR_RES_HEAT_RANGE_ADDR, BME680_ADDR_RES_HEAT_VAL_ADDR, BME680_ADDR_SENS_CONF_START,
+    BME680_CALIB_READ_ATTEMPTS, BME680_CHIP_ID, BME680_CHIP_ID_ADDR, BME680_COEFF_ADDR1,
+    BME680_COEFF_ADDR1_LEN, BME680_COEFF_ADDR2, BME680_COEFF_ADDR2_LEN, BME680_CONF_HEAT_CTRL_ADDR,
+    BME680_CONF_ODR_RUN_GAS_NBC_ADDR, BME680_CONF_T_P_MODE_ADDR, BME680_FIELD0_ADDR,
+    BME680_FIELD_LENGTH, BME680_HEATER_STEPS, BME680_MODE_MSK, BME680_NEW_DATA_MSK,
+    BME680_POLL_PERIOD_MS, BME680_REG_BUFFER_LENGTH, BME680_RESET_PERIOD, BME680_SOFT_RESET_ADDR,
+    BME680_SOFT_RESET_CMD, BME680_TMP_BUFFER_LENGTH, BME680_VARIANT_ID_ADDR, BME688_FIELDS,
+};
+use core::marker::PhantomData;
+use core::time::Duration;
+use embedded_hal_async::delay::DelayNs;
+use embedded_hal_async::i2c::I2c;
+use log::{debug, error, info};
+
+/// Async driver for the BME680 environmental sensor
+pub struct Bme680Async<I2C, D> {
+    i2c: I2C,
+    delay: PhantomData<D>,
+    dev_id: I2CAddress,
+    variant: ChipVariant,
+    calib: CalibData,
+    compensation: Compensation,
+    tph_sett: TphSett,
+    gas_sett: GasSett,
+    power_mode: PowerMode,
+}
+
+impl<I2C, D> Bme680Async<I2C, D>
+where
+    D: DelayNs,
+    I2C: I2c,
+{
+    async fn read_byte(&mut self, reg_addr: u8) -> Result<u8, I2C::Error, I2C::Error> {
+        let mut buf = [0; 1];
+        self.read_bytes(reg_addr, &mut buf).await?;
+        Ok(buf[0])
+    }
+
+    async fn read_bytes(
+        &mut self,
+        reg_addr: u8,
+        buf: &mut [u8],
+    ) -> Result<(), I2C::Error, I2C::Error> {
+        read_bytes(&mut self.i2c, self.dev_id, reg_addr, buf).await
+    }
+
+    pub async fn soft_reset(
+        i2c: &mut I2C,
+        delay: &mut D,
+        dev_id: I2CAddress,
+    ) -> Result<(), I2C::Error, I2C::Error> {
+        let tmp_buff: [u8; 2] = [BME680_SOFT_RESET_ADDR, BME680_SOFT_RESET_CMD];
+
+        i2c.write(dev_id.addr(), &tmp_buff)
+            .await
+            .map_err(Error::I2CWrite)?;
+
+        delay.delay_ms(BME680_RESET_PERIOD as u32).await;
+        Ok(())
+    }
+
+    pub async fn init(
+        i2c: I2C,
+        delay: &mut D,
+        dev_id: I2CAddress,
+    ) -> Result<Bme680Async<I2C, D>, I2C::Error, I2C::Error> {
+        Bme680Async::init_inner(i2c, delay, dev_id, None, BME680_CALIB_READ_ATTEMPTS).await
+    }
+
+    /// Initializes the sensor, reading the calibration data up to `attempts` times until
+    /// it is plausible
+    ///
+    /// See `Bme680::init_with_calib_attempts`.
+    pub async fn init_with_calib_attempts(
+        i2c: I2C,
+        delay: &mut D,
+        dev_id: I2CAddress,
+        attempts: u8,
+    ) -> Result<Bme680Async<I2C, D>, I2C::Error, I2C::Error> {
+        Bme680Async::init_inner(i2c, delay, dev_id, None, attempts).await
+    }
+
+    /// Initializes the sensor using stored calibration data instead of reading it
+    ///
+    /// See `Bme680::init_with_interface_and_calib`.
+    pub async fn init_with_calib(
+        i2c: I2C,
+        delay: &mut D,
+        dev_id: I2CAddress,
+        calib: CalibData,
+    ) -> Result<Bme680Async<I2C, D>, I2C::Error, I2C::Error> {
+        Bme680Async::init_inner(i2c, delay, dev_id, Some(calib), 1).await
+    }
+
+    async fn init_inner(
+        mut i2c: I2C,
+        delay: &mut D,
+        dev_id: I2CAddress,
+        calib: Option<CalibData>,
+        attempts: u8,
+    ) -> Result<Bme680Async<I2C, D>, I2C::Error, I2C::Error> {
+        Bme680Async::soft_reset(&mut i2c, delay, dev_id).await?;
+
+        debug!("Reading chip id");
+        let mut chip_id = [0; 1];
+        read_bytes(&mut i2c, dev_id, BME680_CHIP_ID_ADDR, &mut chip_id).await?;
+        let chip_id = chip_id[0];
+        debug!("Chip id: {}", chip_id);
+
+        if chip_id == BME680_CHIP_ID {
+            let mut variant = [0; 1];
+            read_bytes(&mut i2c, dev_id, BME680_VARIANT_ID_ADDR, &mut variant).await?;
+            let variant = ChipVariant::from(variant[0]);
+            debug!("Chip variant: {:?}", variant);
+            let mut dev = Bme680Async {
+                i2c,
+                delay: PhantomData,
+                dev_id,
+                variant,
+                calib: Default::default(),
+                compensation: Compensation::default(),
+                power_mode: PowerMode::ForcedMode,
+                tph_sett: Default::default(),
+                gas_sett: Default::default(),
+            };
+            dev.calib = match calib {
+                Some(calib) => validate_calib(calib)?,
+                None => dev.read_calib_data(delay, attempts).await?,
+            };
+            debug!("Calib data {:?}", dev.calib);
+            info!("Finished device init");
+            Ok(dev)
+        } else {
+            error!("Device does not match chip id {}", BME680_CHIP_ID);
+            Err(Error::DeviceNotFound)
+        }
+    }
+
+    /// Captures the driver state, to be restored via [`resume`](Self::resume)
+    pub fn snapshot(&self) -> Snapshot {
+        Snapshot {
+            address: Some(self.dev_id),
+            variant: self.variant,
+            calib: self.calib,
+            compensation: self.compensation,
+            tph_sett: self.tph_sett,
+            gas_sett: self.gas_sett,
+            power_mode: self.power_mode,
+        }
+    }
+
+    /// Rebuilds the driver from a snapshot without accessing the sensor
+    ///
+    /// See `Bme680::resume`, fails with `SnapshotError::MissingAddress` for snapshots taken
+    /// via an interface other than I²C.
+    pub fn resume(i2c: I2C, snapshot: &Snapshot) -> core::result::Result<Self, SnapshotError> {
+        Ok(Bme680Async {
+            i2c,
+            delay: PhantomData,
+            dev_id: snapshot.address.ok_or(SnapshotError::MissingAddress)?,
+            variant: snapshot.variant,
+            calib: snapshot.calib,
+            compensation: snapshot.compensation,
+            tph_sett: snapshot.tph_sett,
+            gas_sett: snapshot.gas_sett,
+            power_mode: snapshot.power_mode,
+        })
+    }
+
+    /// Variant of the sensor, read during initialization
+    pub fn chip_variant(&self) -> ChipVariant {
+        self.variant
+    }
+
+    /// Calibration data read during initialization
+    pub fn calib_data(&self) -> CalibData {
+        self.calib
+    }
+
+    /// Formulas used to compensate the sensor data
+    pub fn compensation(&self) -> Compensation {
+        self.compensation
+    }
+
+    /// Selects the formulas used to compensate the sensor data, integer by default
+    pub fn set_compensation(&mut self, compensation: Compensation) {
+        self.compensation = compensation;
+    }
+
+    /// Puts the sensor to sleep and returns the I²C bus
+    ///
+    /// A borrowed bus can be used by passing `&mut I2C` to [`init`](Self::init).
+    pub async fn release(mut self, delay: &mut D) -> Result<I2C, I2C::Error, I2C::Error> {
+        self.set_sensor_mode(delay, PowerMode::SleepMode).await?;
+        Ok(self.i2c)
+    }
+
+    async fn bme680_set_regs(&mut self, reg: &[(u8, u8)]) -> Result<(), I2C::Error, I2C::Error> {
+        if reg.is_empty() || reg.len() > BME680_TMP_BUFFER_LENGTH / 2 {
+            return Err(Error::InvalidLength);
+        }
+
+        debug!("Setting registers {:?}", reg);
+        let mut buff = [0u8; BME680_TMP_BUFFER_LENGTH];
+        self.i2c
+            .write(self.dev_id.addr(), burst(reg, &mut buff))
+            .await
+            .map_err(Error::I2CWrite)
+    }
+
+    /// Set the settings to be used during the sensor measurements
+    ///
+    /// Fails with [`Error::DefinePwrMode`] if gas settings are given while the sensor is in
+    /// parallel or sequential mode, whose heater profile they would overwrite.
+    pub async fn set_sensor_settings(
+        &mut self,
+        delay: &mut D,
+        settings: Settings,
+    ) -> Result<(), I2C::Error, I2C::Error> {
+        let (sensor_settings, desired_settings) = settings;
+        let tph_sett = sensor_settings.tph_sett;
+        let gas_sett = sensor_settings.gas_sett;
+
+        // The heater set-points hold the profile of parallel or sequential mode
+        if desired_settings.contains(DesiredSensorSettings::GAS_MEAS_SEL)
+            && matches!(
+                self.power_mode,
+                PowerMode::ParallelMode | PowerMode::SequentialMode
+            )
+        {
+            return Err(Error::DefinePwrMode);
+        }
+
+        // Configuration registers may only be written in sleep mode
+        self.set_sensor_mode(delay, PowerMode::SleepMode).await?;
+
+        let mut conf_regs: [u8; BME680_REG_BUFFER_LENGTH] = [0; BME680_REG_BUFFER_LENGTH];
+        self.read_bytes(BME680_CONF_HEAT_CTRL_ADDR, &mut conf_regs)
+            .await?;
+
+        let (reg, element_index) = sensor_settings_regs(
+            &self.calib,
+            self.variant,
+            desired_settings,
+            &tph_sett,
+            &gas_sett,
+            &conf_regs,
+        )?;
+        if element_index > 0 {
+            self.bme680_set_regs(&reg[0..element_index]).await?;
+        }
+
+        self.tph_sett = tph_sett;
+        self.gas_sett.record_settings(desired_settings, &gas_sett);
+        Ok(())
+    }
+
+    /// Configures the heater set-points of a heater profile
+    ///
+    /// See [`Bme680::set_heater_profile`](crate::Bme680::set_heater_profile).
+    pub async fn set_heater_profile(
+        &mut self,
+        delay: &mut D,
+        ambient_temperature: i8,
+        steps: &[HeaterStep],
+    ) -> Result<(), I2C::Error, I2C::Error> {
+        let (reg, element_index) = heater_profile_regs(&self.calib, ambient_temperature, steps)?;
+
+        self.set_sensor_mode(delay, PowerMode::SleepMode).await?;
+        self.bme680_set_regs(&reg[0..element_index]).await?;
+        self.gas_sett.record_heater_profile(
+            ambient_temperature,
+            steps.iter().map(|step| (step.temperature, step.duration)),
+        );
+        // The selected set-point may have been reprogrammed
+        self.gas_sett.record_heater_step(self.gas_sett.nb_conv);
+        Ok(())
+    }
+
+    /// Selects the heater set-point used by the next gas measurements
+    pub async fn select_heater_step(
+        &mut self,
+        delay: &mut D,
+        nb_conv: u8,
+    ) -> Result<(), I2C::Error, I2C::Error> {
+        self.set_sensor_mode(delay, PowerMode::SleepMode).await?;
+
+        let ctrl_gas_1 = self.read_byte(BME680_CONF_ODR_RUN_GAS_NBC_ADDR).await?;
+        let reg = heater_step_select_reg(ctrl_gas_1, nb_conv)?;
+        self.bme680_set_regs(&[reg]).await?;
+        self.gas_sett.record_heater_step(nb_conv);
+        Ok(())
+    }
+
+    /// Configures the heater profile used in parallel mode, BME688 only
+    ///
+    /// See [`Bme680::set_parallel_heater_profile`](crate::Bme680::set_parallel_heater_profile).
+    pub async fn set_parallel_heater_profile(
+        &mut self,
+        delay: &mut D,
+        ambient_temperature: i8,
+        shared_duration: Duration,
+        steps: &[ParallelHeaterStep],
+    ) -> Result<(), I2C::Error, I2C::Error> {
+        if !self.variant.supports(PowerMode::ParallelMode) {
+            return Err(Error::DefinePwrMode);
+        }
+
+        self.set_sensor_mode(delay, PowerMode::SleepMode).await?;
+
+        let ctrl_gas_1 = self.read_byte(BME680_CONF_ODR_RUN_GAS_NBC_ADDR).await?;
+        let (reg, element_index) = parallel_profile_regs(
+            &self.calib,
+            self.variant,
+            ctrl_gas_1,
+            ambient_temperature,
+            shared_duration,
+            steps,
+        )?;
+        self.bme680_set_regs(&reg[0..element_index]).await?;
+        self.gas_sett.record_heater_profile(
+            ambient_temperature,
+            steps
+                .iter()
+                .map(|step| (step.temperature, shared_duration * step.multiplier as u32)),
+        );
+        self.gas_sett.record_all_heater_steps(steps.len());
+        self.gas_sett.heatr_dur = Some(shared_duration);
+        Ok(())
+    }
+
+    /// Configures the heater profile used in sequential mode, BME688 only
+    ///
+    /// See [`Bme680::set_sequential_heater_profile`](crate::Bme680::set_sequential_heater_profile).
+    pub async fn set_sequential_heater_profile(
+        &mut self,
+        delay: &mut D,
+        ambient_temperature: i8,
+        steps: &[HeaterStep],
+    ) -> Result<(), I2C::Error, I2C::Error> {
+        if !self.variant.supports(PowerMode::SequentialMode) {
+            return Err(Error::DefinePwrMode);
+        }
+
+        self.set_sensor_mode(delay, PowerMode::SleepMode).await?;
+
+        let ctrl_gas_1 = self.read_byte(BME680_CONF_ODR_RUN_GAS_NBC_ADDR).await?;
+        let (reg, element_index) = sequential_profile_regs(
+            &self.calib,
+            self.variant,
+            ctrl_gas_1,
+            ambient_temperature,
+            steps,
+        )?;
+        self.bme680_set_regs(&reg[0..element_index]).await?;
+        self.gas_sett.record_heater_profile(
+            ambient_temperature,
+            steps.iter().map(|step| (step.temperature, step.duration)),
+        );
+        self.gas_sett.record_all_heater_steps(steps.len());
+        Ok(())
+    }
+
+    /// Retrieve settings from sensor registers
+    ///
+    /// # Arguments
+    ///
+    /// * `desired_settings` - Settings to be retrieved. Setting values may stay `None` if not retrieved.
+    pub async fn get_sensor_settings(
+        &mut self,
+        desired_settings: DesiredSensorSettings,
+    ) -> Result<SensorSettings, I2C::Error, I2C::Error> {
+        let mut data_array: [u8; BME680_REG_BUFFER_LENGTH] = [0; BME680_REG_BUFFER_LENGTH];
+        let mut sensor_settings: SensorSettings = Default::default();
+        sensor_settings.tph_sett.temperature_offset = self.tph_sett.temperature_offset;
+        sensor_settings.tph_sett.temperature_offset_centi = self.tph_sett.temperature_offset_centi;
+
+        self.read_bytes(BME680_CONF_HEAT_CTRL_ADDR, &mut data_array)
+            .await?;
+
+        if desired_settings.contains(DesiredSensorSettings::GAS_MEAS_SEL) {
+            sensor_settings.gas_sett = self.get_gas_config(data_array[1]).await?;
+        }
+
+        sensor_settings_from_regs(
+            self.variant,
+            desired_settings,
+            &data_array,
+            &mut sensor_settings,
+        );
+
+        Ok(sensor_settings)
+    }
+
+    /// Set the sensor into a certain power mode
+    ///
+    /// # Arguments
+    ///
+    /// * `target_power_mode` - Desired target power mode
+    pub async fn set_sensor_mode(
+        &mut self,
+        delay: &mut D,
+        target_power_mode: PowerMode,
+    ) -> Result<(), I2C::Error, I2C::Error> {
+        if !self.variant.supports(target_power_mode) {
+            return Err(Error::DefinePwrMode);
+        }
+
+        let mut tmp_pow_mode: u8;
+
+        // Call repeatedly until in sleep
+        loop {
+            tmp_pow_mode = self.read_byte(BME680_CONF_T_P_MODE_ADDR).await?;
+
+            // Put to sleep before changing mode
+            let current_power_mode = PowerMode::from(tmp_pow_mode & BME680_MODE_MSK);
+
+            debug!("Current power mode: {:?}", current_power_mode);
+
+            if current_power_mode == PowerMode::SleepMode {
+                break;
+            }
+
+            // Set to sleep
+            tmp_pow_mode &= !BME680_MODE_MSK;
+            debug!("Setting to sleep tmp_pow_mode: {}", tmp_pow_mode);
+            self.bme680_set_regs(&[(BME680_CONF_T_P_MODE_ADDR, tmp_pow_mode)])
+                .await?;
+            delay.delay_ms(BME680_POLL_PERIOD_MS as u32).await;
+        }
+
+        // Already in sleep
+        if target_power_mode != PowerMode::SleepMode {
+            tmp_pow_mode = tmp_pow_mode & !BME680_MODE_MSK | target_power_mode.value();
+            debug!("Already in sleep Target power mode: {}", tmp_pow_mode);
+            self.bme680_set_regs(&[(BME680_CONF_T_P_MODE_ADDR, tmp_pow_mode)])
+                .await?;
+        }
+        self.power_mode = target_power_mode;
+        Ok(())
+    }
+
+    /// Retrieve current sensor power mode via registers
+    pub async fn get_sensor_mode(&mut self) -> Result<PowerMode, I2C::Error, I2C::Error> {
+        let regs = self.read_byte(BME680_CONF_T_P_MODE_ADDR).await?;
+        let mode = regs & BME680_MODE_MSK;
+        Ok(PowerMode::from(mode))
+    }
+
+    pub fn get_profile_dur(
+        &self,
+        sensor_settings: &SensorSettings,
+    ) -> Result<Duration, I2C::Error, I2C::Error> {
+        Ok(profile_dur(sensor_settings))
+    }
+
+    /// Reads the calibration data until it is plausible, at most `attempts` times
+    async fn read_calib_data(
+        &mut self,
+        delay: &mut D,
+        attempts: u8,
+    ) -> Result<CalibData, I2C::Error, I2C::Error> {
+        let mut attempt = 1;
+        loop {
+            debug!("Reading calib data, attempt {}", attempt);
+            let calib = self.get_calib_data().await?;
+            match validate_calib(calib) {
+                Err(Error::InvalidCalibration(_)) if attempt < attempts => {
+                    delay.delay_ms(BME680_POLL_PERIOD_MS as u32).await;
+                    attempt += 1;
+                }
+                result => return result,
+            }
+        }
+    }
+
+    async fn get_calib_data(&mut self) -> Result<CalibData, I2C::Error, I2C::Error> {
+        let mut coeff_array: [u8; BME680_COEFF_ADDR1_LEN + BME680_COEFF_ADDR2_LEN] =
+            [0; BME680_COEFF_ADDR1_LEN + BME680_COEFF_ADDR2_LEN];
+
+        self.read_bytes(
+            BME680_COEFF_ADDR1,
+            &mut coeff_array[0..(BME680_COEFF_ADDR1_LEN - 1)],
+        )
+        .await?;
+
+        self.read_bytes(
+            BME680_COEFF_ADDR2,
+            &mut coeff_array
+                [BME680_COEFF_ADDR1_LEN..(BME680_COEFF_ADDR1_LEN + BME680_COEFF_ADDR2_LEN - 1)],
+        )
+        .await?;
+
+        let res_heat_range = self.read_byte(BME680_ADDR_RES_HEAT_RANGE_ADDR).await?;
+        let res_heat_val = self.read_byte(BME680_ADDR_RES_HEAT_VAL_ADDR).await?;
+        let range_sw_err = self.read_byte(BME680_ADDR_RANGE_SW_ERR_ADDR).await?;
+
+        Ok(calib_data_from_regs(
+            &coeff_array,
+            res_heat_range,
+            res_heat_val,
+            range_sw_err,
+        ))
+    }
+
+    async fn get_gas_config(&mut self, ctrl_gas_1: u8) -> Result<GasSett, I2C::Error, I2C::Error> {
+        let mut res_heat = [0; BME680_HEATER_STEPS];
+        let mut gas_wait = [0; BME680_HEATER_STEPS];
+        self.read_bytes(BME680_ADDR_SENS_CONF_START, &mut res_heat)
+            .await?;
+        self.read_bytes(BME680_ADDR_GAS_CONF_START, &mut gas_wait)
+            .await?;
+
+        Ok(gas_config_from_regs(
+            &self.calib,
+            self.gas_sett.ambient_temperature,
+            ctrl_gas_1,
+            &res_heat,
+            &gas_wait,
+        ))
+    }
+
+    /// Reads all fields with new data, ordered by measurement index
+    pub async fn get_all_field_data(&mut self) -> Result<FieldDataSet, I2C::Error, I2C::Error> {
+        let mut buff = [0; BME680_FIELD_LENGTH * BME688_FIELDS];
+        self.read_bytes(BME680_FIELD0_ADDR, &mut buff).await?;
+        debug!("Field data read {:?}", buff);
+
+        field_data_set_from_regs(
+            &buff,
+            &self.calib,
+            self.variant,
+            self.compensation,
+            &self.tph_sett,
+        )
+        .map_err(Error::Compensation)
+    }
+
+    /// Retrieve the current sensor informations
+    pub async fn get_sensor_data(
+        &mut self,
+        delay: &mut D,
+    ) -> Result<(FieldData, FieldDataCondition), I2C::Error, I2C::Error> {
+        let (raw, condition) = self.get_raw_sensor_data(delay).await?;
+        let data = field_data_from_raw(&raw, &self.calib, self.compensation, &self.tph_sett)
+            .map_err(Error::Compensation)?;
+        Ok((data, condition))
+    }
+
+    /// Retrieve the current sensor values without compensating them
+    pub async fn get_raw_sensor_data(
+        &mut self,
+        delay: &mut D,
+    ) -> Result<(RawFieldData, FieldDataCondition), I2C::Error, I2C::Error> {
+        let mut buff: [u8; BME680_FIELD_LENGTH] = [0; BME680_FIELD_LENGTH];
+        let mut raw: RawFieldData = Default::default();
+
+        const TRIES: u8 = 10;
+        for _ in 0..TRIES {
+            self.read_bytes(BME680_FIELD0_ADDR, &mut buff[..self.variant.field_length()])
+                .await?;
+
+            debug!("Field data read {:?}, len: {}", buff, buff.len());
+
+            raw = raw_field_data_from_regs(&buff, self.variant);
+
+            if raw.status & BME680_NEW_DATA_MSK != 0 {
+                return Ok((raw, FieldDataCondition::NewData));
+            }
+
+            delay.delay_ms(BME680_POLL_PERIOD_MS as u32).await;
+        }
+        Ok((raw, FieldDataCondition::Unchanged))
+    }
+}
+
+async fn read_bytes<I2C: I2c>(
+    i2c: &mut I2C,
+    dev_id: I2CAddress,
+    reg_addr: u8,
+    buf: &mut [u8],
+) -> Result<(), I2C::Error, I2C::Error> {
+    // Repeated start, so the bus is not released between address and data
+    i2c.write_read(dev_id.addr(), &[reg_addr], buf)
+        .await
+        .map_err(Error::I2CRead)
+}
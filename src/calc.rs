@@ -1,6 +1,72 @@
+//! Compensation formulas of the Bosch reference driver.
+//!
+//! The functions convert raw ADC values into physical values and heater settings into
+//! register values. They are used by the driver, but can also be applied directly, e.g.
+//! to recompute readings offline.
+
 use crate::CalibData;
+use core::convert::TryFrom;
 use core::time::Duration;
 
+/// Largest value of the 20-bit temperature and pressure ADCs
+const ADC_MAX_20BIT: u32 = 0xfffff;
+/// Largest value of the 10-bit gas resistance ADC
+const ADC_MAX_10BIT: u16 = 0x3ff;
+/// Largest gas range
+const GAS_RANGE_MAX: u8 = 15;
+
+/// Reason raw values could not be compensated, e.g. after a corrupted bus read
+///
+/// The name of the measured quantity is given, e.g. `"pressure"`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CompensationError {
+    /// The raw value is outside the range of the ADC
+    OutOfRange(&'static str),
+    /// An intermediate result exceeds the range of the reference arithmetic
+    Overflow(&'static str),
+    /// A divisor of the formula is zero
+    DivisionByZero(&'static str),
+}
+
+/// Gas resistance lookup tables of the BME680, indexed by gas range
+const GAS_LOOKUP_TABLE1: [u32; 16] = [
+    2147483647u32,
+    2147483647u32,
+    2147483647u32,
+    2147483647u32,
+    2147483647u32,
+    2126008810u32,
+    2147483647u32,
+    2130303777u32,
+    2147483647u32,
+    2147483647u32,
+    2143188679u32,
+    2136746228u32,
+    2147483647u32,
+    2126008810u32,
+    2147483647u32,
+    2147483647u32,
+];
+const GAS_LOOKUP_TABLE2: [u32; 16] = [
+    4096000000u32,
+    2048000000u32,
+    1024000000u32,
+    512000000u32,
+    255744255u32,
+    127110228u32,
+    64000000u32,
+    32258064u32,
+    16016016u32,
+    8000000u32,
+    4000000u32,
+    2000000u32,
+    1000000u32,
+    500000u32,
+    250000u32,
+    125000u32,
+];
+
+/// Bosch compensation formulas
 pub struct Calc {}
 
 impl Calc {
@@ -24,7 +90,7 @@ impl Calc {
         // TODO replace once https://github.com/rust-lang/rust/pull/50167 has been merged
         const MILLIS_PER_SEC: u64 = 1_000;
         const NANOS_PER_MILLI: u64 = 1_000_000;
-        let mut dur = (duration.as_secs() as u64 * MILLIS_PER_SEC)
+        let mut dur = (duration.as_secs() * MILLIS_PER_SEC)
             + (duration.subsec_nanos() as u64 / NANOS_PER_MILLI);
         if dur as i32 >= 0xfc0i32 {
             0xffu8 // Max duration
@@ -40,132 +106,426 @@ impl Calc {
         }
     }
 
+    /// Inverse of `calc_heater_res`
+    ///
+    /// Returns the temperature in degree celsius in the middle of the range of temperatures
+    /// mapped to `res_heat`, and half the width of that range as quantisation error.
+    pub fn calc_heater_temp(calib: &CalibData, amb_temp: i8, res_heat: u8) -> (u16, u16) {
+        let mut range: Option<(u16, u16)> = None;
+        let mut nearest = (0u16, u8::MAX);
+        for temp in 0..=400u16 {
+            let res = Calc::calc_heater_res(calib, amb_temp, temp);
+            if res == res_heat {
+                range = Some((range.map_or(temp, |(min, _)| min), temp));
+            }
+            let distance = (res as i16 - res_heat as i16).unsigned_abs() as u8;
+            if distance < nearest.1 {
+                nearest = (temp, distance);
+            }
+        }
+
+        match range {
+            Some((min, max)) => ((min + max) / 2, (max - min).div_ceil(2)),
+            // Not reachable by any temperature, e.g. written by another driver
+            None => (nearest.0, 0),
+        }
+    }
+
+    /// Inverse of `calc_heater_dur`
+    ///
+    /// Returns the heating duration encoded by `gas_wait` and the quantisation error, i.e.
+    /// how much longer the requested duration may have been.
+    pub fn calc_heater_duration(gas_wait: u8) -> (Duration, Duration) {
+        let factor = 1u64 << (2 * (gas_wait >> 6));
+        let dur = (gas_wait & 0x3f) as u64 * factor;
+        (
+            Duration::from_millis(dur),
+            Duration::from_millis(factor - 1),
+        )
+    }
+
     ///
     /// * `calib` - Calibration data used during initalization
     /// * `temp_adc`
     /// * `temp_offset` - If set, the temperature t_fine will be increased by given
-    ///                   value in celsius. Temperature offset in Celsius, e.g. 4, -8, 1.25
+    ///   value in celsius. Temperature offset in Celsius, e.g. 4, -8, 1.25
     pub fn calc_temperature(
         calib: &CalibData,
         temp_adc: u32,
         temp_offset: Option<f32>,
-    ) -> (i16, i32) {
+    ) -> Result<(i16, i32), CompensationError> {
+        check_adc(temp_adc, "temperature")?;
         let var1: i64 = (temp_adc as i64 >> 3) - ((calib.par_t1 as i64) << 1);
         let var2: i64 = (var1 * (calib.par_t2 as i64)) >> 11;
         let var3: i64 = ((var1 >> 1) * (var1 >> 1)) >> 12;
         let var3: i64 = (var3 * ((calib.par_t3 as i64) << 4)) >> 14;
 
         let temp_offset = match temp_offset {
-            None => 0i32,
-            Some(offset) if offset == 0.0 => 0i32,
+            None => 0i64,
+            Some(0.0) => 0i64,
             Some(offset) => {
-                let signum: i32 = if offset.gt(&0.0) { 1 } else { -1 };
-                signum * (((((offset * 100.0) as i32).abs() << 8) - 128) / 5)
+                let signum: i64 = if offset.gt(&0.0) { 1 } else { -1 };
+                signum * (((((offset * 100.0) as i32 as i64).abs() << 8) - 128) / 5)
             }
         };
 
-        let t_fine: i32 = (var2 + var3) as i32 + temp_offset;
-        let calc_temp: i16 = (((t_fine * 5) + 128) >> 8) as i16;
-        (calc_temp, t_fine)
-    }
-
-    pub fn calc_pressure(calib: &CalibData, t_fine: i32, pres_adc: u32) -> u32 {
-        let mut var1: i32 = (t_fine >> 1) - 64000;
-        let mut var2: i32 = ((((var1 >> 2) * (var1 >> 2)) >> 11) * calib.par_p6 as i32) >> 2;
-        var2 += (var1 * (calib.par_p5 as i32)) << 1;
-        var2 = (var2 >> 2i32) + ((calib.par_p4 as i32) << 16i32);
-        var1 = (((((var1 >> 2i32) * (var1 >> 2i32)) >> 13i32) * ((calib.par_p3 as i32) << 5i32))
-            >> 3i32)
-            + ((calib.par_p2 as i32 * var1) >> 1i32);
-        var1 >>= 18i32;
-        var1 = ((32768i32 + var1) * calib.par_p1 as i32) >> 15i32;
-        let mut pressure_comp: i32 = 1048576u32.wrapping_sub(pres_adc) as i32;
-        pressure_comp = ((pressure_comp - (var2 >> 12i32)) as u32).wrapping_mul(3125u32) as i32;
-        if pressure_comp >= 0x40000000i32 {
-            pressure_comp = ((pressure_comp as u32).wrapping_div(var1 as u32) << 1i32) as i32;
+        let overflow = |_| CompensationError::Overflow("temperature");
+        let t_fine = i32::try_from(var2 + var3 + temp_offset).map_err(overflow)?;
+        let calc_temp = i16::try_from(((t_fine as i64 * 5) + 128) >> 8).map_err(overflow)?;
+        Ok((calc_temp, t_fine))
+    }
+
+    /// Pressure in pascal
+    ///
+    /// Fails with `Overflow` where the 32-bit arithmetic of the reference implementation
+    /// overflows or the pressure is negative. The cubic term is multiplied to 64 bits, so that
+    /// the full range of the sensor up to 1100 hPa is compensated.
+    pub fn calc_pressure(
+        calib: &CalibData,
+        t_fine: i32,
+        pres_adc: u32,
+    ) -> Result<u32, CompensationError> {
+        check_adc(pres_adc, "pressure")?;
+        let add = |a: i32, b: i32| {
+            a.checked_add(b)
+                .ok_or(CompensationError::Overflow("pressure"))
+        };
+        let mul = |a: i32, b: i32| {
+            a.checked_mul(b)
+                .ok_or(CompensationError::Overflow("pressure"))
+        };
+        let var1: i32 = (t_fine >> 1) - 64000;
+        let var2: i32 = mul(mul(var1 >> 2, var1 >> 2)? >> 11, calib.par_p6 as i32)? >> 2;
+        let var2: i32 = add(var2, mul(mul(var1, calib.par_p5 as i32)?, 2)?)?;
+        let var2: i32 = add(var2 >> 2, (calib.par_p4 as i32) << 16)?;
+        let var1: i32 = add(
+            mul(mul(var1 >> 2, var1 >> 2)? >> 13, (calib.par_p3 as i32) << 5)? >> 3,
+            mul(calib.par_p2 as i32, var1)? >> 1,
+        )? >> 18;
+        let var1: i32 = mul(32768 + var1, calib.par_p1 as i32)? >> 15;
+        let pressure_comp: i32 = mul(1048576 - pres_adc as i32 - (var2 >> 12), 3125)?;
+        if var1 == 0 {
+            return Err(CompensationError::DivisionByZero("pressure"));
+        }
+        if pressure_comp < 0 || var1 < 0 {
+            // Negative pressure
+            return Err(CompensationError::Overflow("pressure"));
+        }
+        let pressure_comp: i32 = if pressure_comp >= 0x40000000 {
+            mul(pressure_comp / var1, 2)?
         } else {
-            pressure_comp = ((pressure_comp << 1i32) as u32).wrapping_div(var1 as u32) as i32;
+            (pressure_comp << 1) / var1
+        };
+        let var1: i32 = mul(
+            calib.par_p9 as i32,
+            mul(pressure_comp >> 3, pressure_comp >> 3)? >> 13,
+        )? >> 12;
+        let var2: i32 = mul(pressure_comp >> 2, calib.par_p8 as i32)? >> 13;
+        // The cube exceeds 32 bits from about 1065 hPa, where the reference overflows, so it is
+        // multiplied to 64 bits. pressure_comp is positive here.
+        let cube = (pressure_comp >> 8) as u32;
+        let (hi, lo) = mul_wide(
+            cube.checked_mul(cube)
+                .ok_or(CompensationError::Overflow("pressure"))?,
+            cube.checked_mul(calib.par_p10 as u32)
+                .ok_or(CompensationError::Overflow("pressure"))?,
+        );
+        if hi >> 16 != 0 {
+            return Err(CompensationError::Overflow("pressure"));
         }
-        var1 = (calib.par_p9 as i32
-            * (((pressure_comp >> 3i32) * (pressure_comp >> 3i32)) >> 13i32))
-            >> 12i32;
-        var2 = ((pressure_comp >> 2i32) * calib.par_p8 as i32) >> 13i32;
-        let var3: i32 = ((pressure_comp >> 8i32)
-            * (pressure_comp >> 8i32)
-            * (pressure_comp >> 8i32)
-            * calib.par_p10 as i32)
-            >> 17i32;
-        pressure_comp += (var1 + var2 + var3 + ((calib.par_p7 as i32) << 7i32)) >> 4i32;
-        pressure_comp as u32
-    }
-
-    pub fn calc_humidity(calib: &CalibData, t_fine: i32, hum_adc: u16) -> u32 {
-        let temp_scaled: i32 = (t_fine * 5i32 + 128i32) >> 8i32;
+        let var3: i32 = (hi << 15 | lo >> 17) as i32;
+        let pressure_comp: i32 = add(
+            pressure_comp,
+            add(add(var1, var2)?, add(var3, (calib.par_p7 as i32) << 7)?)? >> 4,
+        )?;
+        u32::try_from(pressure_comp).map_err(|_| CompensationError::Overflow("pressure"))
+    }
+
+    /// Humidity in 0.001 % relative humidity
+    ///
+    /// Fails with `Overflow` where the 32-bit arithmetic of the reference implementation
+    /// overflows.
+    pub fn calc_humidity(
+        calib: &CalibData,
+        t_fine: i32,
+        hum_adc: u16,
+    ) -> Result<u32, CompensationError> {
+        let add = |a: i32, b: i32| {
+            a.checked_add(b)
+                .ok_or(CompensationError::Overflow("humidity"))
+        };
+        let mul = |a: i32, b: i32| {
+            a.checked_mul(b)
+                .ok_or(CompensationError::Overflow("humidity"))
+        };
+        let temp_scaled: i32 = add(mul(t_fine, 5)?, 128)? >> 8;
         let var1: i32 = hum_adc as i32
-            - calib.par_h1 as i32 * 16i32
-            - ((temp_scaled * calib.par_h3 as i32 / 100i32) >> 1i32);
-        let var2: i32 = (calib.par_h2 as i32
-            * (temp_scaled * calib.par_h4 as i32 / 100i32
-                + ((temp_scaled * (temp_scaled * calib.par_h5 as i32 / 100i32)) >> 6i32) / 100i32
-                + (1i32 << 14i32)))
-            >> 10i32;
-        let var3: i32 = var1 * var2;
-        let var4: i32 = (calib.par_h6 as i32) << 7i32;
-        let var4: i32 = (var4 + temp_scaled * calib.par_h7 as i32 / 100i32) >> 4i32;
-        let var5: i32 = ((var3 >> 14i32) * (var3 >> 14i32)) >> 10i32;
-        let var6: i32 = (var4 * var5) >> 1i32;
-        let mut calc_hum: i32 = (((var3 + var6) >> 10i32) * 1000i32) >> 12i32;
-        if calc_hum > 100000i32 {
-            calc_hum = 100000i32;
-        } else if calc_hum < 0i32 {
-            calc_hum = 0i32;
+            - calib.par_h1 as i32 * 16
+            - ((mul(temp_scaled, calib.par_h3 as i32)? / 100) >> 1);
+        let var2: i32 = add(
+            add(
+                mul(temp_scaled, calib.par_h4 as i32)? / 100,
+                (mul(temp_scaled, mul(temp_scaled, calib.par_h5 as i32)? / 100)? >> 6) / 100,
+            )?,
+            1 << 14,
+        )?;
+        let var2: i32 = mul(calib.par_h2 as i32, var2)? >> 10;
+        let var3: i32 = mul(var1, var2)?;
+        let var4: i32 = add(
+            (calib.par_h6 as i32) << 7,
+            mul(temp_scaled, calib.par_h7 as i32)? / 100,
+        )? >> 4;
+        let var5: i32 = mul(var3 >> 14, var3 >> 14)? >> 10;
+        let var6: i32 = mul(var4, var5)? >> 1;
+        let calc_hum: i32 = mul(add(var3, var6)? >> 10, 1000)? >> 12;
+        Ok(calc_hum.clamp(0, 100000) as u32)
+    }
+
+    /// Gas resistance of the BME680 in ohm
+    pub fn calc_gas_resistance(
+        calib: &CalibData,
+        gas_res_adc: u16,
+        gas_range: u8,
+    ) -> Result<u32, CompensationError> {
+        check_gas_adc(gas_res_adc, gas_range)?;
+        let var1: i64 = ((1340 + 5 * calib.range_sw_err as i64)
+            * GAS_LOOKUP_TABLE1[gas_range as usize] as i64)
+            >> 16;
+        let var2: i64 = ((gas_res_adc as i64) << 15) - 16777216 + var1;
+        if var2 == 0 {
+            return Err(CompensationError::DivisionByZero("gas resistance"));
         }
-        calc_hum as u32
-    }
-
-    pub fn calc_gas_resistance(calib: &CalibData, gas_res_adc: u16, gas_range: u8) -> u32 {
-        let lookup_table1: [u32; 16] = [
-            2147483647u32,
-            2147483647u32,
-            2147483647u32,
-            2147483647u32,
-            2147483647u32,
-            2126008810u32,
-            2147483647u32,
-            2130303777u32,
-            2147483647u32,
-            2147483647u32,
-            2143188679u32,
-            2136746228u32,
-            2147483647u32,
-            2126008810u32,
-            2147483647u32,
-            2147483647u32,
+        let var3: i64 = (GAS_LOOKUP_TABLE2[gas_range as usize] as i64 * var1) >> 9;
+        u32::try_from((var3 + (var2 >> 1)) / var2)
+            .map_err(|_| CompensationError::Overflow("gas resistance"))
+    }
+
+    /// Gas resistance of the BME688, which does not depend on calibration data
+    pub fn calc_gas_resistance_high(
+        gas_res_adc: u16,
+        gas_range: u8,
+    ) -> Result<u32, CompensationError> {
+        check_gas_adc(gas_res_adc, gas_range)?;
+        let var1: u32 = 262144u32 >> gas_range;
+        let var2: i32 = 4096 + (gas_res_adc as i32 - 512) * 3;
+        // Multiplying by 10000 and then by 100 instead of 1000000 prevents an overflow
+        let calc_gas_res: u32 = (10000u32 * var1) / var2 as u32;
+        Ok(calc_gas_res * 100)
+    }
+
+    /// Variant of `calc_temperature` using only 32-bit arithmetic, with identical results
+    ///
+    /// * `temp_adc` - 20-bit temperature ADC value
+    /// * `temp_offset` - Temperature offset in 0.01 degree celsius, e.g. 125 for 1.25 °C
+    pub fn calc_temperature_i32(
+        calib: &CalibData,
+        temp_adc: u32,
+        temp_offset: i16,
+    ) -> Result<(i16, i32), CompensationError> {
+        check_adc(temp_adc, "temperature")?;
+        let var1: i32 = (temp_adc >> 3) as i32 - ((calib.par_t1 as i32) << 1);
+        // var1 * par_t2 exceeds 32 bits, so it is multiplied in two parts
+        let var2: i32 =
+            (var1 >> 11) * calib.par_t2 as i32 + (((var1 & 0x7ff) * calib.par_t2 as i32) >> 11);
+        // The square is below 2^32 for 20-bit ADC values
+        let var3: u32 = (var1 >> 1).unsigned_abs() * (var1 >> 1).unsigned_abs();
+        let var3: i32 = ((var3 >> 12) as i32 * ((calib.par_t3 as i32) << 4)) >> 14;
+
+        let temp_offset = match temp_offset {
+            0 => 0i32,
+            offset => offset.signum() as i32 * ((((offset as i32).abs() << 8) - 128) / 5),
+        };
+
+        let t_fine: i32 = var2 + var3 + temp_offset;
+        let calc_temp = i16::try_from(((t_fine * 5) + 128) >> 8)
+            .map_err(|_| CompensationError::Overflow("temperature"))?;
+        Ok((calc_temp, t_fine))
+    }
+
+    /// Variant of `calc_gas_resistance` using only 32-bit arithmetic, with identical results
+    pub fn calc_gas_resistance_i32(
+        calib: &CalibData,
+        gas_res_adc: u16,
+        gas_range: u8,
+    ) -> Result<u32, CompensationError> {
+        check_gas_adc(gas_res_adc, gas_range)?;
+        let lookup1 = GAS_LOOKUP_TABLE1[gas_range as usize];
+        let range_sw_err = 1340 + 5 * calib.range_sw_err as u32;
+        let var1: u32 =
+            range_sw_err * (lookup1 >> 16) + ((range_sw_err * (lookup1 & 0xffff)) >> 16);
+        // Positive, as var1 exceeds 16777216 for all gas ranges
+        let var2: u32 = ((gas_res_adc as u32) << 15) + var1 - 16777216;
+        let (hi, lo) = mul_wide(GAS_LOOKUP_TABLE2[gas_range as usize], var1);
+        let (hi, lo) = (hi >> 9, lo >> 9 | hi << 23);
+        let sum = lo.wrapping_add(var2 >> 1);
+        let hi = if sum < lo { hi + 1 } else { hi };
+        Ok(div_wide(hi, sum, var2))
+    }
+
+    /// Floating-point variant of `calc_temperature`
+    ///
+    /// Returns the temperature in degree celsius and t_fine.
+    pub fn calc_temperature_float(
+        calib: &CalibData,
+        temp_adc: u32,
+        temp_offset: Option<f32>,
+    ) -> Result<(f32, f32), CompensationError> {
+        check_adc(temp_adc, "temperature")?;
+        let var1: f32 =
+            (temp_adc as f32 / 16384.0 - calib.par_t1 as f32 / 1024.0) * calib.par_t2 as f32;
+        let var2: f32 = (temp_adc as f32 / 131072.0 - calib.par_t1 as f32 / 8192.0)
+            * (temp_adc as f32 / 131072.0 - calib.par_t1 as f32 / 8192.0)
+            * (calib.par_t3 as f32 * 16.0);
+
+        // t_fine is 5120 times the temperature
+        let t_fine: f32 = var1 + var2 + temp_offset.unwrap_or(0.0) * 5120.0;
+        if !t_fine.is_finite() {
+            return Err(CompensationError::Overflow("temperature"));
+        }
+        Ok((t_fine / 5120.0, t_fine))
+    }
+
+    /// Floating-point variant of `calc_pressure`, returns the pressure in pascal
+    pub fn calc_pressure_float(
+        calib: &CalibData,
+        t_fine: f32,
+        pres_adc: u32,
+    ) -> Result<f32, CompensationError> {
+        check_adc(pres_adc, "pressure")?;
+        let mut var1: f32 = t_fine / 2.0 - 64000.0;
+        let mut var2: f32 = var1 * var1 * (calib.par_p6 as f32 / 131072.0);
+        var2 += var1 * calib.par_p5 as f32 * 2.0;
+        var2 = var2 / 4.0 + calib.par_p4 as f32 * 65536.0;
+        var1 =
+            (calib.par_p3 as f32 * var1 * var1 / 16384.0 + calib.par_p2 as f32 * var1) / 524288.0;
+        var1 = (1.0 + var1 / 32768.0) * calib.par_p1 as f32;
+        if var1 as i32 == 0 {
+            return Err(CompensationError::DivisionByZero("pressure"));
+        }
+
+        let mut pressure_comp: f32 = 1048576.0 - pres_adc as f32;
+        pressure_comp = (pressure_comp - var2 / 4096.0) * 6250.0 / var1;
+        var1 = calib.par_p9 as f32 * pressure_comp * pressure_comp / 2147483648.0;
+        var2 = pressure_comp * (calib.par_p8 as f32 / 32768.0);
+        let var3: f32 = (pressure_comp / 256.0)
+            * (pressure_comp / 256.0)
+            * (pressure_comp / 256.0)
+            * (calib.par_p10 as f32 / 131072.0);
+        let pressure_comp =
+            pressure_comp + (var1 + var2 + var3 + calib.par_p7 as f32 * 128.0) / 16.0;
+        // Also rejects NaN
+        if !(0.0..=u32::MAX as f32).contains(&pressure_comp) {
+            return Err(CompensationError::Overflow("pressure"));
+        }
+        Ok(pressure_comp)
+    }
+
+    /// Floating-point variant of `calc_humidity`, returns the humidity in % relative humidity
+    pub fn calc_humidity_float(
+        calib: &CalibData,
+        t_fine: f32,
+        hum_adc: u16,
+    ) -> Result<f32, CompensationError> {
+        let temp_comp: f32 = t_fine / 5120.0;
+        let var1: f32 =
+            hum_adc as f32 - (calib.par_h1 as f32 * 16.0 + calib.par_h3 as f32 / 2.0 * temp_comp);
+        let var2: f32 = var1
+            * (calib.par_h2 as f32 / 262144.0
+                * (1.0
+                    + calib.par_h4 as f32 / 16384.0 * temp_comp
+                    + calib.par_h5 as f32 / 1048576.0 * temp_comp * temp_comp));
+        let var3: f32 = calib.par_h6 as f32 / 16384.0;
+        let var4: f32 = calib.par_h7 as f32 / 2097152.0;
+        let calc_hum: f32 = var2 + (var3 + var4 * temp_comp) * var2 * var2;
+        if calc_hum.is_nan() {
+            return Err(CompensationError::Overflow("humidity"));
+        }
+        Ok(calc_hum.clamp(0.0, 100.0))
+    }
+
+    /// Floating-point variant of `calc_gas_resistance`, returns the gas resistance in ohm
+    pub fn calc_gas_resistance_float(
+        calib: &CalibData,
+        gas_res_adc: u16,
+        gas_range: u8,
+    ) -> Result<f32, CompensationError> {
+        check_gas_adc(gas_res_adc, gas_range)?;
+        let lookup_k1_range: [f32; 16] = [
+            0.0, 0.0, 0.0, 0.0, 0.0, -1.0, 0.0, -0.8, 0.0, 0.0, -0.2, -0.5, 0.0, -1.0, 0.0, 0.0,
         ];
-        let lookup_table2: [u32; 16] = [
-            4096000000u32,
-            2048000000u32,
-            1024000000u32,
-            512000000u32,
-            255744255u32,
-            127110228u32,
-            64000000u32,
-            32258064u32,
-            16016016u32,
-            8000000u32,
-            4000000u32,
-            2000000u32,
-            1,
-            500000u32,
-            250000u32,
-            125000u32,
+        let lookup_k2_range: [f32; 16] = [
+            0.0, 0.0, 0.0, 0.0, 0.1, 0.7, 0.0, -0.8, -0.1, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0,
         ];
-        let var1: i64 = ((1340 + 5 * calib.range_sw_err as i64)
-            * lookup_table1[gas_range as usize] as i64)
-            >> 16;
-        let var2: u64 = (((gas_res_adc as i64) << 15) - 16777216 + var1) as u64;
-        let var3: i64 = (lookup_table2[gas_range as usize] as i64 * var1) >> 9;
-        let calc_gas_res: u32 = ((var3 + ((var2 as i64) >> 1i64)) / var2 as i64) as u32;
-        calc_gas_res
+        let var1: f32 = 1340.0 + 5.0 * calib.range_sw_err as f32;
+        let var2: f32 = var1 * (1.0 + lookup_k1_range[gas_range as usize] / 100.0);
+        let var3: f32 = 1.0 + lookup_k2_range[gas_range as usize] / 100.0;
+        let var4: f32 = var3
+            * 0.000000125
+            * (1u32 << gas_range) as f32
+            * ((gas_res_adc as f32 - 512.0) / var2 + 1.0);
+        if var4 == 0.0 {
+            return Err(CompensationError::DivisionByZero("gas resistance"));
+        }
+        Ok(1.0 / var4)
+    }
+
+    /// Floating-point variant of `calc_gas_resistance_high`, returns the gas resistance in ohm
+    pub fn calc_gas_resistance_high_float(
+        gas_res_adc: u16,
+        gas_range: u8,
+    ) -> Result<f32, CompensationError> {
+        check_gas_adc(gas_res_adc, gas_range)?;
+        let var1: u32 = 262144u32 >> gas_range;
+        let var2: i32 = 4096 + (gas_res_adc as i32 - 512) * 3;
+        Ok(1000000.0 * var1 as f32 / var2 as f32)
+    }
+}
+
+/// Fails if a temperature or pressure ADC value exceeds 20 bits
+fn check_adc(adc: u32, quantity: &'static str) -> Result<(), CompensationError> {
+    if adc > ADC_MAX_20BIT {
+        return Err(CompensationError::OutOfRange(quantity));
+    }
+    Ok(())
+}
+
+/// Fails if the gas resistance ADC value exceeds 10 bits or the gas range is unknown
+fn check_gas_adc(gas_res_adc: u16, gas_range: u8) -> Result<(), CompensationError> {
+    if gas_res_adc > ADC_MAX_10BIT {
+        return Err(CompensationError::OutOfRange("gas resistance"));
+    }
+    if gas_range > GAS_RANGE_MAX {
+        return Err(CompensationError::OutOfRange("gas range"));
+    }
+    Ok(())
+}
+
+/// Multiplies two 32-bit values, returning the high and low word of the product
+fn mul_wide(a: u32, b: u32) -> (u32, u32) {
+    let (a_hi, a_lo) = (a >> 16, a & 0xffff);
+    let (b_hi, b_lo) = (b >> 16, b & 0xffff);
+    let lo_lo = a_lo * b_lo;
+    let lo_hi = a_lo * b_hi;
+    let hi_lo = a_hi * b_lo;
+    let mid = (lo_lo >> 16) + (lo_hi & 0xffff) + (hi_lo & 0xffff);
+    let lo = (lo_lo & 0xffff) | mid << 16;
+    let hi = a_hi * b_hi + (lo_hi >> 16) + (hi_lo >> 16) + (mid >> 16);
+    (hi, lo)
+}
+
+/// Divides the 64-bit value given by its high and low word by `divisor`
+///
+/// The quotient has to fit into 32 bits, i.e. `hi` has to be below `divisor`.
+fn div_wide(hi: u32, lo: u32, divisor: u32) -> u32 {
+    let (mut remainder, mut quotient) = (hi, lo);
+    for _ in 0..32 {
+        let carry = remainder >> 31;
+        remainder = remainder << 1 | quotient >> 31;
+        quotient <<= 1;
+        if carry != 0 || remainder >= divisor {
+            remainder = remainder.wrapping_sub(divisor);
+            quotient |= 1;
+        }
     }
+    quotient
 }
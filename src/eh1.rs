@@ -0,0 +1,103 @@
+//! Adapters for the embedded-hal 1.0 traits.
+//!
+//! The driver is written against the embedded-hal 0.2 blocking traits. HALs that only
+//! implement embedded-hal 1.0 can wrap their `I2c` bus and `DelayNs` provider in
+//! [`I2cAdapter`] and [`DelayAdapter`] to use them with [`Bme680`](crate::Bme680).
+//!
+//! ```no_run
+//! use bme680::eh1::{DelayAdapter, I2cAdapter};
+//! use bme680::{Bme680, I2CAddress};
+//! # use embedded_hal_1::delay::DelayNs;
+//! # use embedded_hal_1::i2c::{ErrorType, I2c, Operation};
+//! # struct I2cdev;
+//! # impl ErrorType for I2cdev {
+//! #     type Error = core::convert::Infallible;
+//! # }
+//! # impl I2c for I2cdev {
+//! #     fn transaction(&mut self, _addr: u8, _ops: &mut [Operation<'_>]) -> Result<(), Self::Error> {
+//! #         Ok(())
+//! #     }
+//! # }
+//! # struct Delay;
+//! # impl DelayNs for Delay {
+//! #     fn delay_ns(&mut self, _ns: u32) {}
+//! # }
+//!
+//! let i2c = I2cAdapter::new(I2cdev);
+//! let mut delayer = DelayAdapter::new(Delay);
+//! let dev = Bme680::init(i2c, &mut delayer, I2CAddress::Primary);
+//! ```
+
+use crate::hal::blocking::delay::DelayMs;
+use crate::hal::blocking::i2c::{Read, Write, WriteRead};
+use embedded_hal_1::delay::DelayNs;
+use embedded_hal_1::i2c::I2c;
+
+/// Wraps an embedded-hal 1.0 `I2c` bus so it can be used by the driver
+#[derive(Debug)]
+pub struct I2cAdapter<I2C> {
+    i2c: I2C,
+}
+
+impl<I2C> I2cAdapter<I2C> {
+    pub fn new(i2c: I2C) -> I2cAdapter<I2C> {
+        I2cAdapter { i2c }
+    }
+
+    /// Returns the wrapped bus
+    pub fn into_inner(self) -> I2C {
+        self.i2c
+    }
+}
+
+impl<I2C: I2c> Read for I2cAdapter<I2C> {
+    type Error = I2C::Error;
+
+    fn read(&mut self, address: u8, buffer: &mut [u8]) -> Result<(), Self::Error> {
+        self.i2c.read(address, buffer)
+    }
+}
+
+impl<I2C: I2c> Write for I2cAdapter<I2C> {
+    type Error = I2C::Error;
+
+    fn write(&mut self, address: u8, bytes: &[u8]) -> Result<(), Self::Error> {
+        self.i2c.write(address, bytes)
+    }
+}
+
+impl<I2C: I2c> WriteRead for I2cAdapter<I2C> {
+    type Error = I2C::Error;
+
+    fn write_read(
+        &mut self,
+        address: u8,
+        bytes: &[u8],
+        buffer: &mut [u8],
+    ) -> Result<(), Self::Error> {
+        self.i2c.write_read(address, bytes, buffer)
+    }
+}
+
+/// Wraps an embedded-hal 1.0 `DelayNs` provider so it can be used by the driver
+#[derive(Debug)]
+pub struct DelayAdapter<D> {
+    delay: D,
+}
+
+impl<D> DelayAdapter<D> {
+    pub fn new(delay: D) -> DelayAdapter<D> {
+        DelayAdapter { delay }
+    }
+
+    /// Returns the wrapped delay provider
+    pub fn into_inner(self) -> D {
+        self.delay
+    }
+}
+
+impl<D: DelayNs> DelayMs<u8> for DelayAdapter<D> {
+    fn delay_ms(&mut self, ms: u8) {
+        self.delay.delay_ms(ms as u32);
+    }
+}
@@ -0,0 +1,362 @@
+use crate::hal::blocking::i2c::{Write, WriteRead};
+use crate::hal::blocking::spi;
+use crate::hal::digital::v2::OutputPin;
+use crate::{Error, I2CAddress, Result, BME680_TMP_BUFFER_LENGTH};
+use core::cell::RefCell;
+
+/// Status register holding the SPI memory page bit
+const BME680_MEM_PAGE_ADDR: u8 = 0x73;
+/// `spi_mem_page` value selecting registers `0x80..=0xFF`, the default after reset
+const BME680_MEM_PAGE0: u8 = 0x00;
+/// `spi_mem_page` value selecting registers `0x00..=0x7F`
+const BME680_MEM_PAGE1: u8 = 0x10;
+
+const BME680_SPI_RD_MSK: u8 = 0x80;
+const BME680_SPI_WR_MSK: u8 = 0x7f;
+
+/// Interleaves register addresses and values for a burst write
+///
+/// `reg` must not contain more than `buff.len() / 2` pairs.
+pub(crate) fn burst<'a>(reg: &[(u8, u8)], buff: &'a mut [u8]) -> &'a [u8] {
+    for (i, (reg_addr, reg_data)) in reg.iter().enumerate() {
+        buff[2 * i] = *reg_addr;
+        buff[2 * i + 1] = *reg_data;
+    }
+    &buff[..2 * reg.len()]
+}
+
+/// Register level access to the sensor
+///
+/// `Bme680` accesses the sensor exclusively through this trait. [`I2cInterface`] and
+/// [`SpiInterface`] are provided, custom transports such as bus bridges, test doubles or
+/// trace recorders can be plugged in via `Bme680::init_with_interface`.
+///
+/// Registers are always addressed by their I²C register addresses.
+///
+/// # Example
+/// ```
+/// use bme680::{Bme680, Interface, Result};
+/// # use embedded_hal::blocking::delay::DelayMs;
+/// # struct Delay;
+/// # impl DelayMs<u8> for Delay {
+/// #     fn delay_ms(&mut self, _ms: u8) {}
+/// # }
+///
+/// /// In-memory register map
+/// struct Registers([u8; 256]);
+///
+/// impl Interface for Registers {
+///     type ReadError = ();
+///     type WriteError = ();
+///
+///     fn read_registers(&mut self, reg_addr: u8, buf: &mut [u8]) -> Result<(), (), ()> {
+///         let start = reg_addr as usize;
+///         buf.copy_from_slice(&self.0[start..start + buf.len()]);
+///         Ok(())
+///     }
+///
+///     fn write_registers(&mut self, reg: &[(u8, u8)]) -> Result<(), (), ()> {
+///         for (reg_addr, reg_data) in reg {
+///             self.0[*reg_addr as usize] = *reg_data;
+///         }
+///         Ok(())
+///     }
+/// }
+///
+/// let mut registers = Registers([0; 256]);
+/// // chip id
+/// registers.0[0xd0] = 0x61;
+/// # // plausible calibration data
+/// # registers.0[0x8a..0xa1].copy_from_slice(&[0x91, 0x66, 0x03, 0x00, 0x2a, 0x8e, 0x12, 0xd7, 0x58, 0x00, 0xd0, 0x1b, 0x74, 0xff, 0x30, 0x1e, 0x00, 0x00, 0xfd, 0xff, 0x7a, 0xf4, 0x1e]);
+/// # registers.0[0xe1..0xef].copy_from_slice(&[0x3f, 0x4a, 0x31, 0x00, 0x2d, 0x14, 0x78, 0x9c, 0x44, 0x66, 0xbc, 0xd0, 0xe2, 0x12]);
+/// # registers.0[0x00] = 40;
+/// # registers.0[0x02] = 0x10;
+/// # registers.0[0x04] = 0x10;
+/// let dev = Bme680::init_with_interface(registers, &mut Delay).unwrap();
+/// ```
+pub trait Interface {
+    /// Error returned when reading from the bus fails
+    type ReadError;
+    /// Error returned when writing to the bus fails
+    type WriteError;
+
+    /// Reads `buf.len()` consecutive registers starting at `reg_addr`
+    fn read_registers(
+        &mut self,
+        reg_addr: u8,
+        buf: &mut [u8],
+    ) -> Result<(), Self::ReadError, Self::WriteError>;
+
+    /// Writes the given register address and value pairs
+    ///
+    /// Implementations should write all pairs in a single burst where the bus allows it.
+    fn write_registers(
+        &mut self,
+        reg: &[(u8, u8)],
+    ) -> Result<(), Self::ReadError, Self::WriteError>;
+
+    /// Reads the register at `reg_addr`
+    fn read_register(&mut self, reg_addr: u8) -> Result<u8, Self::ReadError, Self::WriteError> {
+        let mut buf = [0; 1];
+        self.read_registers(reg_addr, &mut buf)?;
+        Ok(buf[0])
+    }
+
+    /// I²C address of the sensor, `None` unless connected via I²C
+    fn i2c_address(&self) -> Option<I2CAddress> {
+        None
+    }
+}
+
+/// I²C interface to the sensor
+#[derive(Debug)]
+pub struct I2cInterface<I2C> {
+    i2c: I2C,
+    dev_id: I2CAddress,
+}
+
+impl<I2C> I2cInterface<I2C> {
+    pub fn new(i2c: I2C, dev_id: I2CAddress) -> I2cInterface<I2C> {
+        I2cInterface { i2c, dev_id }
+    }
+
+    /// I²C address of the sensor
+    pub fn address(&self) -> I2CAddress {
+        self.dev_id
+    }
+
+    /// Returns the underlying bus
+    pub fn release(self) -> I2C {
+        self.i2c
+    }
+}
+
+/// Mutably borrowed I²C bus
+///
+/// Allows the driver to use a bus that is owned elsewhere, see `Bme680::init_borrowed`.
+#[derive(Debug)]
+pub struct I2cRef<'a, I2C>(pub &'a mut I2C);
+
+impl<'a, I2C: Write> Write for I2cRef<'a, I2C> {
+    type Error = <I2C as Write>::Error;
+
+    fn write(&mut self, address: u8, bytes: &[u8]) -> core::result::Result<(), Self::Error> {
+        self.0.write(address, bytes)
+    }
+}
+
+impl<'a, I2C: WriteRead> WriteRead for I2cRef<'a, I2C> {
+    type Error = <I2C as WriteRead>::Error;
+
+    fn write_read(
+        &mut self,
+        address: u8,
+        bytes: &[u8],
+        buffer: &mut [u8],
+    ) -> core::result::Result<(), Self::Error> {
+        self.0.write_read(address, bytes, buffer)
+    }
+}
+
+impl<I2C> Interface for I2cInterface<I2C>
+where
+    I2C: WriteRead + Write,
+{
+    type ReadError = <I2C as WriteRead>::Error;
+    type WriteError = <I2C as Write>::Error;
+
+    fn i2c_address(&self) -> Option<I2CAddress> {
+        Some(self.dev_id)
+    }
+
+    fn read_registers(
+        &mut self,
+        reg_addr: u8,
+        buf: &mut [u8],
+    ) -> Result<(), Self::ReadError, Self::WriteError> {
+        // Repeated start, so the bus is not released between address and data
+        self.i2c
+            .write_read(self.dev_id.addr(), &[reg_addr], buf)
+            .map_err(Error::I2CRead)
+    }
+
+    fn write_registers(
+        &mut self,
+        reg: &[(u8, u8)],
+    ) -> Result<(), Self::ReadError, Self::WriteError> {
+        let mut buff = [0u8; BME680_TMP_BUFFER_LENGTH];
+        for chunk in reg.chunks(BME680_TMP_BUFFER_LENGTH / 2) {
+            self.i2c
+                .write(self.dev_id.addr(), burst(chunk, &mut buff))
+                .map_err(Error::I2CWrite)?;
+        }
+        Ok(())
+    }
+}
+
+/// I²C bus shared by several drivers within a single thread or interrupt priority
+///
+/// Each bus access borrows the bus only for the duration of the transaction, so multiple
+/// sensors and other peripherals can use the same bus. Bus accesses must not be attempted
+/// while the bus is borrowed elsewhere, i.e. from an interrupt handler, as this panics.
+/// For sharing across threads or interrupt priorities wrap an embedded-hal 1.0 bus in an
+/// [embedded-hal-bus](https://docs.rs/embedded-hal-bus) device such as `CriticalSectionDevice`
+/// or `MutexDevice` and use it via the `eh1::I2cAdapter`.
+#[derive(Debug)]
+pub struct SharedI2c<'a, I2C>(&'a RefCell<I2C>);
+
+impl<'a, I2C> SharedI2c<'a, I2C> {
+    pub fn new(bus: &'a RefCell<I2C>) -> SharedI2c<'a, I2C> {
+        SharedI2c(bus)
+    }
+}
+
+impl<'a, I2C: Write> Write for SharedI2c<'a, I2C> {
+    type Error = <I2C as Write>::Error;
+
+    fn write(&mut self, address: u8, bytes: &[u8]) -> core::result::Result<(), Self::Error> {
+        self.0.borrow_mut().write(address, bytes)
+    }
+}
+
+impl<'a, I2C: WriteRead> WriteRead for SharedI2c<'a, I2C> {
+    type Error = <I2C as WriteRead>::Error;
+
+    fn write_read(
+        &mut self,
+        address: u8,
+        bytes: &[u8],
+        buffer: &mut [u8],
+    ) -> core::result::Result<(), Self::Error> {
+        self.0.borrow_mut().write_read(address, bytes, buffer)
+    }
+}
+
+/// Errors of the SPI interface
+#[derive(Debug)]
+pub enum SpiError<SPI, CS> {
+    /// SPI bus error
+    Spi(SPI),
+    /// Error setting the chip select pin
+    ChipSelect(CS),
+}
+
+/// SPI interface to the sensor
+///
+/// In SPI mode the register address is only 7 bits wide, bit 7 selects between read and
+/// write access. The register map is therefore split into two pages which are selected
+/// by the `spi_mem_page` bit of register `0x73`. The interface switches pages as needed,
+/// so registers are addressed by their I²C addresses.
+#[derive(Debug)]
+pub struct SpiInterface<SPI, CS> {
+    spi: SPI,
+    cs: CS,
+    /// Currently selected memory page, unknown until first selected
+    mem_page: Option<u8>,
+}
+
+impl<SPI, CS> SpiInterface<SPI, CS> {
+    pub fn new(spi: SPI, cs: CS) -> SpiInterface<SPI, CS> {
+        SpiInterface {
+            spi,
+            cs,
+            mem_page: None,
+        }
+    }
+
+    /// Returns the underlying bus and chip select pin
+    pub fn release(self) -> (SPI, CS) {
+        (self.spi, self.cs)
+    }
+}
+
+impl<SPI, CS, E, PinE> SpiInterface<SPI, CS>
+where
+    SPI: spi::Transfer<u8, Error = E> + spi::Write<u8, Error = E>,
+    CS: OutputPin<Error = PinE>,
+{
+    /// Runs `f` while the chip select pin is asserted
+    fn with_cs<T, F>(&mut self, f: F) -> core::result::Result<T, SpiError<E, PinE>>
+    where
+        F: FnOnce(&mut SPI) -> core::result::Result<T, E>,
+    {
+        self.cs.set_low().map_err(SpiError::ChipSelect)?;
+        let res = f(&mut self.spi).map_err(SpiError::Spi);
+        self.cs.set_high().map_err(SpiError::ChipSelect)?;
+        res
+    }
+
+    /// Selects the memory page containing `reg_addr`
+    fn set_mem_page(&mut self, reg_addr: u8) -> Result<(), SpiError<E, PinE>, SpiError<E, PinE>> {
+        let mem_page = if reg_addr > 0x7f {
+            BME680_MEM_PAGE0
+        } else {
+            BME680_MEM_PAGE1
+        };
+
+        if self.mem_page == Some(mem_page) {
+            return Ok(());
+        }
+
+        // spi_mem_page is the only writable bit of the status register, so there is no need
+        // for a read-modify-write, which would not work before 3-wire mode is enabled.
+        self.with_cs(|spi| spi.write(&[BME680_MEM_PAGE_ADDR & BME680_SPI_WR_MSK, mem_page]))
+            .map_err(Error::I2CWrite)?;
+
+        self.mem_page = Some(mem_page);
+        Ok(())
+    }
+}
+
+impl<SPI, CS, E, PinE> Interface for SpiInterface<SPI, CS>
+where
+    SPI: spi::Transfer<u8, Error = E> + spi::Write<u8, Error = E>,
+    CS: OutputPin<Error = PinE>,
+{
+    type ReadError = SpiError<E, PinE>;
+    type WriteError = SpiError<E, PinE>;
+
+    fn read_registers(
+        &mut self,
+        reg_addr: u8,
+        buf: &mut [u8],
+    ) -> Result<(), Self::ReadError, Self::WriteError> {
+        self.set_mem_page(reg_addr)?;
+
+        for byte in buf.iter_mut() {
+            *byte = 0;
+        }
+        self.with_cs(|spi| {
+            spi.write(&[reg_addr | BME680_SPI_RD_MSK])?;
+            spi.transfer(buf).map(|_| ())
+        })
+        .map_err(Error::I2CRead)
+    }
+
+    fn write_registers(
+        &mut self,
+        reg: &[(u8, u8)],
+    ) -> Result<(), Self::ReadError, Self::WriteError> {
+        let mut remaining = reg;
+        while let Some((first, _)) = remaining.first() {
+            // Registers of the same memory page are written in a single burst
+            let same_page = remaining
+                .iter()
+                .take_while(|(reg_addr, _)| (*reg_addr > 0x7f) == (*first > 0x7f))
+                .count();
+            let (page_regs, rest) = remaining.split_at(same_page);
+
+            self.set_mem_page(*first)?;
+            self.with_cs(|spi| {
+                for (reg_addr, reg_data) in page_regs {
+                    spi.write(&[*reg_addr & BME680_SPI_WR_MSK, *reg_data])?;
+                }
+                Ok(())
+            })
+            .map_err(Error::I2CWrite)?;
+
+            remaining = rest;
+        }
+        Ok(())
+    }
+}
@@ -1,8 +1,17 @@
 //! This crate is a pure Rust implementation for the BME680 environmental sensor.
-//! The library can be used to read the gas, pressure, humidity and temperature sensors via I²C.
+//! The library can be used to read the gas, pressure, humidity and temperature sensors via I²C or SPI.
 //!
-//! The library uses the embedded-hal crate to abstract reading and writing via I²C.
+//! The library uses the embedded-hal crate to abstract reading and writing via I²C or SPI.
+//! Use `Bme680::init` for sensors connected via I²C and `Bme680::init_spi` or `Bme680::init_spi_3wire`
+//! for sensors connected via SPI. Other transports can be used by implementing the [`Interface`] trait.
 //! In the examples you can find a demo how to use the library in Linux using the linux-embedded-hal crate (e.g. on a RPI).
+//! HALs implementing embedded-hal 1.0 can be used by enabling the `embedded-hal-1` feature, see the `eh1` module.
+//! An async driver `Bme680Async` built on embedded-hal-async is available with the `async` feature.
+//! Several sensors can share one I²C bus by wrapping it in a [`SharedI2c`], see [Sharing the bus](#sharing-the-bus).
+//! Sensors behind a TCA9548A multiplexer are supported by the `mux` module.
+//! The calibration data can be stored via `CalibData::to_bytes`, or serde with the `serde` feature
+//! deriving `Serialize`/`Deserialize` for `CalibData`, `Snapshot` and the settings,
+//! and be passed to `Bme680::init_with_calib` to skip reading it after waking from deep sleep.
 //! ```no_run
 
 //! extern crate bme680;
@@ -41,16 +50,16 @@
 //! #       }
 //! #   }
 //! #
-//! #   impl i2c::Read for I2cdev {
+//! #   impl i2c::WriteRead for I2cdev {
 //! #       type Error = I2CError;
 //! #
-//! #       fn read<'w>(&mut self, addr: u8, bytes: &'w mut [u8]) -> result::Result<(), Self::Error> {
+//! #       fn write_read<'w>(&mut self, addr: u8, bytes: &'w [u8], buffer: &'w mut [u8]) -> result::Result<(), Self::Error> {
 //! #           Ok(())
 //! #       }
 //! #   }
 //! # }
 //!
-//! fn main() -> result::Result<(), Error<<hal::I2cdev as i2c::Read>::Error, <hal::I2cdev as i2c::Write>::Error>>
+//! fn main() -> result::Result<(), Error<<hal::I2cdev as i2c::WriteRead>::Error, <hal::I2cdev as i2c::Write>::Error>>
 //! {
 //!     // Initialize device
 //!     let i2c = I2cdev {};        // Your I2C device construction will look different, perhaps using I2cdev::new(..)
@@ -80,21 +89,92 @@
 //!     Ok(())
 //! }
 //! ```
+//!
+//! # Sharing the bus
+//!
+//! A sensor at each of the two standard addresses, plus any other peripherals, can share one
+//! I²C bus. Both drivers hold a [`SharedI2c`] referring to the same bus and are used alternately.
+//! For sharing a bus across threads or interrupt priorities use an embedded-hal-bus device such
+//! as `CriticalSectionDevice` or `MutexDevice` via `eh1::I2cAdapter` instead.
+//!
+//! ```no_run
+//! use bme680::*;
+//! use core::cell::RefCell;
+//! use std::thread::sleep;
+//! use std::time::Duration;
+//! # use embedded_hal::blocking::{delay, i2c};
+//! # struct Delay;
+//! # impl delay::DelayMs<u8> for Delay {
+//! #     fn delay_ms(&mut self, _ms: u8) {}
+//! # }
+//! # struct I2cdev;
+//! # impl i2c::Write for I2cdev {
+//! #     type Error = ();
+//! #     fn write(&mut self, _addr: u8, _bytes: &[u8]) -> core::result::Result<(), ()> { Ok(()) }
+//! # }
+//! # impl i2c::WriteRead for I2cdev {
+//! #     type Error = ();
+//! #     fn write_read(&mut self, _addr: u8, _bytes: &[u8], _buffer: &mut [u8]) -> core::result::Result<(), ()> { Ok(()) }
+//! # }
+//!
+//! fn main() -> Result<(), (), ()> {
+//!     let bus = RefCell::new(I2cdev);
+//!     let mut delayer = Delay;
+//!     let mut first = Bme680::init(SharedI2c::new(&bus), &mut delayer, I2CAddress::Primary)?;
+//!     let mut second = Bme680::init(SharedI2c::new(&bus), &mut delayer, I2CAddress::Secondary)?;
+//!
+//!     let settings = SettingsBuilder::new()
+//!         .with_temperature_oversampling(OversamplingSetting::OS8x)
+//!         .with_run_gas(false)
+//!         .build();
+//!     let profile_duration = first.get_profile_dur(&settings.0)?;
+//!     for dev in [&mut first, &mut second].iter_mut() {
+//!         dev.set_sensor_settings(&mut delayer, settings)?;
+//!     }
+//!
+//!     loop {
+//!         for dev in [&mut first, &mut second].iter_mut() {
+//!             dev.set_sensor_mode(&mut delayer, PowerMode::ForcedMode)?;
+//!             sleep(profile_duration);
+//!             let (data, _state) = dev.get_sensor_data(&mut delayer)?;
+//!             println!("Temperature {}°C", data.temperature_celsius());
+//!         }
+//!     }
+//! }
+//! ```
 
 #![no_std]
 #![forbid(unsafe_code)]
 
+pub use self::interface::{I2cInterface, I2cRef, Interface, SharedI2c, SpiError, SpiInterface};
 pub use self::settings::{
-    DesiredSensorSettings, GasSett, IIRFilterSize, OversamplingSetting, SensorSettings, Settings,
-    SettingsBuilder, TphSett,
+    DesiredSensorSettings, GasSett, HeaterSetPoint, HeaterStep, IIRFilterSize, OversamplingSetting,
+    ParallelHeaterStep, SensorSettings, Settings, SettingsBuilder, TphSett,
 };
 
-mod calc;
+pub use self::calc::CompensationError;
+pub use self::sequencer::{FieldDataReader, HeaterScan, HeaterSequencer};
+pub use self::snapshot::{Snapshot, SnapshotError, BME680_SNAPSHOT_LEN};
+
+#[cfg(feature = "async")]
+pub use self::asynch::Bme680Async;
+
+#[cfg(feature = "async")]
+mod asynch;
+pub mod calc;
+#[cfg(feature = "embedded-hal-1")]
+pub mod eh1;
+mod interface;
+pub mod mux;
+mod sequencer;
 mod settings;
+mod snapshot;
 
 use crate::calc::Calc;
 use crate::hal::blocking::delay::DelayMs;
-use crate::hal::blocking::i2c::{Read, Write};
+use crate::hal::blocking::i2c::{Write, WriteRead};
+use crate::hal::blocking::spi;
+use crate::hal::digital::v2::OutputPin;
 
 use core::time::Duration;
 use core::{marker::PhantomData, result};
@@ -103,12 +183,15 @@ use log::{debug, error, info};
 
 /// BME680 General config
 pub const BME680_POLL_PERIOD_MS: u8 = 10;
+/// Number of times the calibration data is read before failing with `Error::InvalidCalibration`
+pub const BME680_CALIB_READ_ATTEMPTS: u8 = 3;
 
 /// BME680 unique chip identifier
 pub const BME680_CHIP_ID: u8 = 0x61;
 
 /// BME680 field_x related defines
-const BME680_FIELD_LENGTH: usize = 15;
+/// Length of a field including the BME688 gas registers
+const BME680_FIELD_LENGTH: usize = 17;
 
 /// BME680 coefficients related defines
 const BME680_COEFF_ADDR1_LEN: usize = 25;
@@ -132,6 +215,14 @@ const BME680_FIELD0_ADDR: u8 = 0x1d;
 /// Heater settings
 const BME680_RES_HEAT0_ADDR: u8 = 0x5a;
 const BME680_GAS_WAIT0_ADDR: u8 = 0x64;
+/// Heating duration shared by the heater steps in parallel mode
+const BME688_GAS_WAIT_SHARED_ADDR: u8 = 0x6e;
+
+/// Number of fields the BME688 writes its results to in parallel and sequential mode
+const BME688_FIELDS: usize = 3;
+
+/// Number of heater set-points
+pub const BME680_HEATER_STEPS: usize = 10;
 
 /// Sensor configuration registers
 const BME680_CONF_HEAT_CTRL_ADDR: u8 = 0x70;
@@ -147,8 +238,14 @@ const BME680_COEFF_ADDR2: u8 = 0xe1;
 /// Chip identifier
 const BME680_CHIP_ID_ADDR: u8 = 0xd0;
 
+/// Variant identifier, distinguishing BME680 and BME688
+const BME680_VARIANT_ID_ADDR: u8 = 0xf0;
+const BME688_VARIANT_ID: u8 = 0x01;
+
 const BME680_SLEEP_MODE: u8 = 0;
 const BME680_FORCED_MODE: u8 = 1;
+const BME688_PARALLEL_MODE: u8 = 2;
+const BME688_SEQUENTIAL_MODE: u8 = 3;
 
 const BME680_RESET_PERIOD: u8 = 10;
 
@@ -156,12 +253,16 @@ const BME680_MODE_MSK: u8 = 0x03;
 const BME680_RSERROR_MSK: u8 = 0xf0;
 const BME680_NEW_DATA_MSK: u8 = 0x80;
 const BME680_GAS_INDEX_MSK: u8 = 0x0f;
+const BME680_NBCONV_MSK: u8 = 0x0f;
+const BME680_RUN_GAS_MSK: u8 = 0x30;
+const BME680_SPI_3W_EN_MSK: u8 = 0x01;
 const BME680_GAS_RANGE_MSK: u8 = 0x0f;
 const BME680_GASM_VALID_MSK: u8 = 0x20;
 const BME680_HEAT_STAB_MSK: u8 = 0x10;
 
 /// Buffer length macro declaration
-const BME680_TMP_BUFFER_LENGTH: usize = 40;
+/// Room for ten heater set-points and the configuration registers
+const BME680_TMP_BUFFER_LENGTH: usize = 52;
 const BME680_REG_BUFFER_LENGTH: usize = 6;
 
 /// All possible errors in this crate
@@ -192,6 +293,15 @@ pub enum Error<R, W> {
     /// Warning Boundary Check
     ///
     BoundaryCheckFailure(&'static str),
+    ///
+    /// Raw values that cannot be compensated, e.g. due to a corrupted bus read
+    ///
+    Compensation(CompensationError),
+    ///
+    /// Implausible calibration data, naming the parameter or register block, see
+    /// `CalibData::validate`
+    ///
+    InvalidCalibration(&'static str),
 }
 
 /// Abbreviates `std::result::Result` type
@@ -201,9 +311,14 @@ pub type Result<T, R, W> = result::Result<T, Error<R, W>>;
 /// Power mode settings
 ///
 #[derive(Debug, PartialEq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum PowerMode {
     SleepMode,
     ForcedMode,
+    /// Continuous TPH and gas measurements across the heater profile, BME688 only
+    ParallelMode,
+    /// Consecutive forced measurements stepping through the heater profile, BME688 only
+    SequentialMode,
 }
 
 impl PowerMode {
@@ -212,6 +327,8 @@ impl PowerMode {
         match power_mode {
             BME680_SLEEP_MODE => PowerMode::SleepMode,
             BME680_FORCED_MODE => PowerMode::ForcedMode,
+            BME688_PARALLEL_MODE => PowerMode::ParallelMode,
+            BME688_SEQUENTIAL_MODE => PowerMode::SequentialMode,
             _ => panic!("Unknown power mode: {}", power_mode),
         }
     }
@@ -220,10 +337,73 @@ impl PowerMode {
         match self {
             PowerMode::SleepMode => BME680_SLEEP_MODE,
             PowerMode::ForcedMode => BME680_FORCED_MODE,
+            PowerMode::ParallelMode => BME688_PARALLEL_MODE,
+            PowerMode::SequentialMode => BME688_SEQUENTIAL_MODE,
         }
     }
 }
 
+/// Sensor variant sharing the BME680 chip id
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ChipVariant {
+    #[default]
+    Bme680,
+    Bme688,
+}
+
+impl ChipVariant {
+    fn from(variant_id: u8) -> Self {
+        match variant_id {
+            BME688_VARIANT_ID => ChipVariant::Bme688,
+            _ => ChipVariant::Bme680,
+        }
+    }
+
+    /// Length of a field, the BME688 gas resistance follows the BME680 gas resistance registers
+    fn field_length(&self) -> usize {
+        match self {
+            ChipVariant::Bme680 => 15,
+            ChipVariant::Bme688 => BME680_FIELD_LENGTH,
+        }
+    }
+
+    /// Whether the power mode is available on this variant
+    fn supports(&self, power_mode: PowerMode) -> bool {
+        match power_mode {
+            PowerMode::SleepMode | PowerMode::ForcedMode => true,
+            PowerMode::ParallelMode | PowerMode::SequentialMode => *self == ChipVariant::Bme688,
+        }
+    }
+
+    /// `run_gas` bits of `ctrl_gas_1` enabling gas measurements
+    fn run_gas_value(&self) -> u8 {
+        match self {
+            ChipVariant::Bme680 => 0x10,
+            ChipVariant::Bme688 => 0x20,
+        }
+    }
+}
+
+/// Formulas used to compensate the raw sensor values
+///
+/// The formulas are provided by Bosch. The floating-point formulas are more accurate but slow
+/// on targets without an FPU.
+#[derive(Debug, PartialEq, Clone, Copy, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Compensation {
+    /// Integer formulas
+    #[default]
+    Integer,
+    /// Integer formulas using only 32-bit arithmetic, see [`compensate_i32`]
+    ///
+    /// Intended for targets such as the Cortex-M0, best combined with
+    /// `SettingsBuilder::with_temperature_offset_centi`.
+    Integer32,
+    /// Floating-point formulas
+    Float,
+}
+
 ///
 /// I2C Slave Address
 /// To determine the slave address of your device you can use `i2cdetect -y 1` on linux.
@@ -232,9 +412,11 @@ impl PowerMode {
 /// Connecting SDO to GND results in slave address 1110110 (0x76); connecting it to V DDIO results in slave
 /// address 1110111 (0x77), which is the same as BMP280’s I2C address.
 ///
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum I2CAddress {
     /// Primary Slave Address 0x76
+    #[default]
     Primary,
     /// Secondary Slave Address 0x77
     Secondary,
@@ -252,14 +434,41 @@ impl I2CAddress {
     }
 }
 
-impl Default for I2CAddress {
-    fn default() -> I2CAddress {
-        I2CAddress::Primary
-    }
+/// Checks whether a BME680 responds at the given address by reading its chip id
+///
+/// Bus errors, e.g. a missing acknowledge, are treated as no sensor being present.
+pub fn is_present<I2C: WriteRead>(i2c: &mut I2C, dev_id: I2CAddress) -> bool {
+    let mut chip_id = [0; 1];
+    i2c.write_read(dev_id.addr(), &[BME680_CHIP_ID_ADDR], &mut chip_id)
+        .is_ok()
+        && chip_id[0] == BME680_CHIP_ID
 }
 
+/// Lists the standard addresses at which a BME680 is present
+///
+/// ```no_run
+/// # use embedded_hal::blocking::i2c::WriteRead;
+/// # fn list<I2C: WriteRead>(mut i2c: I2C) {
+/// for address in bme680::scan(&mut i2c) {
+///     println!("BME680 found at {:#x}", address.addr());
+/// }
+/// # }
+/// ```
+pub fn scan<'a, I2C: WriteRead>(i2c: &'a mut I2C) -> impl Iterator<Item = I2CAddress> + 'a {
+    [I2CAddress::Primary, I2CAddress::Secondary]
+        .iter()
+        .copied()
+        .filter(move |dev_id| is_present(i2c, *dev_id))
+}
+
+/// Length of the serialized calibration data, see [`CalibData::to_bytes`]
+pub const BME680_CALIB_DATA_LEN: usize = 38;
+/// Format version of the serialized calibration data
+const BME680_CALIB_DATA_VERSION: u8 = 1;
+
 /// Calibration data used during initalization
-#[derive(Debug, Default, Copy)]
+#[derive(Debug, Default, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[repr(C)]
 pub struct CalibData {
     pub par_h1: u16,
@@ -296,6 +505,164 @@ impl Clone for CalibData {
     }
 }
 
+/// Reasons stored calibration data cannot be restored
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CalibDataError {
+    /// The data is not [`BME680_CALIB_DATA_LEN`] bytes long
+    InvalidLength,
+    /// The data was stored using an unknown format version
+    UnsupportedVersion(u8),
+}
+
+impl CalibData {
+    /// Serializes the calibration data, e.g. to keep it across deep sleep
+    ///
+    /// The first byte is the format version, followed by the parameters in field order,
+    /// little endian.
+    pub fn to_bytes(&self) -> [u8; BME680_CALIB_DATA_LEN] {
+        let mut bytes = [0; BME680_CALIB_DATA_LEN];
+        let mut len = 0;
+        let mut put = |value: &[u8]| {
+            bytes[len..len + value.len()].copy_from_slice(value);
+            len += value.len();
+        };
+        put(&[BME680_CALIB_DATA_VERSION]);
+        put(&self.par_h1.to_le_bytes());
+        put(&self.par_h2.to_le_bytes());
+        put(&self.par_h3.to_le_bytes());
+        put(&self.par_h4.to_le_bytes());
+        put(&self.par_h5.to_le_bytes());
+        put(&self.par_h6.to_le_bytes());
+        put(&self.par_h7.to_le_bytes());
+        put(&self.par_gh1.to_le_bytes());
+        put(&self.par_gh2.to_le_bytes());
+        put(&self.par_gh3.to_le_bytes());
+        put(&self.par_t1.to_le_bytes());
+        put(&self.par_t2.to_le_bytes());
+        put(&self.par_t3.to_le_bytes());
+        put(&self.par_p1.to_le_bytes());
+        put(&self.par_p2.to_le_bytes());
+        put(&self.par_p3.to_le_bytes());
+        put(&self.par_p4.to_le_bytes());
+        put(&self.par_p5.to_le_bytes());
+        put(&self.par_p6.to_le_bytes());
+        put(&self.par_p7.to_le_bytes());
+        put(&self.par_p8.to_le_bytes());
+        put(&self.par_p9.to_le_bytes());
+        put(&self.par_p10.to_le_bytes());
+        put(&self.res_heat_range.to_le_bytes());
+        put(&self.res_heat_val.to_le_bytes());
+        put(&self.range_sw_err.to_le_bytes());
+        bytes
+    }
+
+    /// Checks for calibration data that cannot have been read from a working sensor,
+    /// returning the name of the offending parameter or register block
+    ///
+    /// Bosch does not specify ranges for the calibration parameters, so only these cases are
+    /// rejected:
+    ///
+    /// - All parameters of the registers `0x8A..=0xA0` or `0xE1..=0xEE` read as `0x00` or as
+    ///   `0xFF`, as returned by a bus without a responding sensor. They are named
+    ///   `"coeff_0x89"` and `"coeff_0xe1"` after the start of the burst read.
+    /// - A zero `par_p1`, which divides by zero in the pressure compensation, or a zero
+    ///   `par_t1`, the temperature ADC value at the reference temperature.
+    pub fn validate(&self) -> result::Result<(), &'static str> {
+        // Parameters of a register block, by register width, as read if every register of the
+        // block holds `byte`
+        let blank = |byte: u8, words: &[u16], bytes: &[u8], nibbles: &[u16]| {
+            words
+                .iter()
+                .all(|&word| word == u16::from_le_bytes([byte, byte]))
+                && bytes.iter().all(|&value| value == byte)
+                && nibbles
+                    .iter()
+                    .all(|&value| value == (byte as u16) << 4 | (byte & 0xf) as u16)
+        };
+        let words = [
+            self.par_t2 as u16,
+            self.par_p1,
+            self.par_p2 as u16,
+            self.par_p4 as u16,
+            self.par_p5 as u16,
+            self.par_p8 as u16,
+            self.par_p9 as u16,
+        ];
+        let bytes = [
+            self.par_t3 as u8,
+            self.par_p3 as u8,
+            self.par_p6 as u8,
+            self.par_p7 as u8,
+            self.par_p10,
+        ];
+        if blank(0x00, &words, &bytes, &[]) || blank(0xff, &words, &bytes, &[]) {
+            return Err("coeff_0x89");
+        }
+        let words = [self.par_t1, self.par_gh2 as u16];
+        let bytes = [
+            self.par_h3 as u8,
+            self.par_h4 as u8,
+            self.par_h5 as u8,
+            self.par_h6,
+            self.par_h7 as u8,
+            self.par_gh1 as u8,
+            self.par_gh3 as u8,
+        ];
+        // par_h1 and par_h2 are 12 bits wide
+        let nibbles = [self.par_h1, self.par_h2];
+        if blank(0x00, &words, &bytes, &nibbles) || blank(0xff, &words, &bytes, &nibbles) {
+            return Err("coeff_0xe1");
+        }
+        if self.par_t1 == 0 {
+            return Err("par_t1");
+        }
+        if self.par_p1 == 0 {
+            return Err("par_p1");
+        }
+        Ok(())
+    }
+
+    /// Restores calibration data serialized by [`to_bytes`](Self::to_bytes)
+    pub fn from_bytes(bytes: &[u8]) -> result::Result<CalibData, CalibDataError> {
+        if bytes.len() != BME680_CALIB_DATA_LEN {
+            return Err(CalibDataError::InvalidLength);
+        }
+        if bytes[0] != BME680_CALIB_DATA_VERSION {
+            return Err(CalibDataError::UnsupportedVersion(bytes[0]));
+        }
+
+        let u16_at = |index: usize| u16::from_le_bytes([bytes[index], bytes[index + 1]]);
+        Ok(CalibData {
+            par_h1: u16_at(1),
+            par_h2: u16_at(3),
+            par_h3: bytes[5] as i8,
+            par_h4: bytes[6] as i8,
+            par_h5: bytes[7] as i8,
+            par_h6: bytes[8],
+            par_h7: bytes[9] as i8,
+            par_gh1: bytes[10] as i8,
+            par_gh2: u16_at(11) as i16,
+            par_gh3: bytes[13] as i8,
+            par_t1: u16_at(14),
+            par_t2: u16_at(16) as i16,
+            par_t3: bytes[18] as i8,
+            par_p1: u16_at(19),
+            par_p2: u16_at(21) as i16,
+            par_p3: bytes[23] as i8,
+            par_p4: u16_at(24) as i16,
+            par_p5: u16_at(26) as i16,
+            par_p6: bytes[28] as i8,
+            par_p7: bytes[29] as i8,
+            par_p8: u16_at(30) as i16,
+            par_p9: u16_at(32) as i16,
+            par_p10: bytes[34],
+            res_heat_range: bytes[35],
+            res_heat_val: bytes[36] as i8,
+            range_sw_err: bytes[37],
+        })
+    }
+}
+
 /// Contains read sensors values  e.g. temperature, pressure, humidity etc.
 #[derive(Debug, Default, Copy)]
 #[repr(C)]
@@ -338,6 +705,16 @@ impl FieldData {
         self.gas_resistance
     }
 
+    /// Heater set-point used for the gas reading
+    pub fn gas_index(&self) -> u8 {
+        self.gas_index
+    }
+
+    /// Index of the measurement, incremented by the sensor with every measurement
+    pub fn meas_index(&self) -> u8 {
+        self.meas_index
+    }
+
     /// Whether a real (and not a dummy) gas reading was performed.
     pub fn gas_valid(&self) -> bool {
         self.status & BME680_GASM_VALID_MSK != 0
@@ -352,6 +729,40 @@ impl FieldData {
     }
 }
 
+/// Uncompensated values of a field as read from the sensor
+///
+/// Compensate them using [`compensate`] or [`compensate_float`].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct RawFieldData {
+    /// Contains new_data, gasm_valid & heat_stab
+    pub status: u8,
+    /// Index of heater profile used
+    pub gas_index: u8,
+    /// Measurement index
+    pub meas_index: u8,
+    pub adc_temp: u32,
+    pub adc_pres: u32,
+    pub adc_hum: u16,
+    pub adc_gas_res: u16,
+    pub gas_range: u8,
+    /// Variant of the sensor, which determines the gas resistance formula
+    pub variant: ChipVariant,
+}
+
+/// New data of the BME688 fields, ordered by measurement index
+#[derive(Debug, Default, Clone, Copy)]
+pub struct FieldDataSet {
+    fields: [FieldData; BME688_FIELDS],
+    len: usize,
+}
+
+impl FieldDataSet {
+    /// Readings ordered from oldest to newest
+    pub fn readings(&self) -> &[FieldData] {
+        &self.fields[..self.len]
+    }
+}
+
 /// Shows if new data is available
 #[derive(PartialEq, Debug)]
 pub enum FieldDataCondition {
@@ -365,52 +776,14 @@ pub enum FieldDataCondition {
     Unchanged,
 }
 
-struct I2CUtil {}
-
-impl I2CUtil {
-    pub fn read_byte<I2C>(
-        i2c: &mut I2C,
-        dev_id: u8,
-        reg_addr: u8,
-    ) -> Result<u8, <I2C as Read>::Error, <I2C as Write>::Error>
-    where
-        I2C: Read + Write,
-    {
-        let mut buf = [0; 1];
-
-        i2c.write(dev_id, &[reg_addr]).map_err(Error::I2CWrite)?;
-
-        match i2c.read(dev_id, &mut buf) {
-            Ok(()) => Ok(buf[0]),
-            Err(e) => Err(Error::I2CRead(e)),
-        }
-    }
-
-    pub fn read_bytes<I2C>(
-        i2c: &mut I2C,
-        dev_id: u8,
-        reg_addr: u8,
-        buf: &mut [u8],
-    ) -> Result<(), <I2C as Read>::Error, <I2C as Write>::Error>
-    where
-        I2C: Read + Write,
-    {
-        i2c.write(dev_id, &[reg_addr]).map_err(Error::I2CWrite)?;
-
-        match i2c.read(dev_id, buf) {
-            Ok(()) => Ok(()),
-            Err(e) => Err(Error::I2CRead(e)),
-        }
-    }
-}
-
 /// Driver for the BME680 environmental sensor
 #[repr(C)]
-pub struct Bme680<I2C, D> {
-    i2c: I2C,
+pub struct Bme680<IF, D> {
+    interface: IF,
     delay: PhantomData<D>,
-    dev_id: I2CAddress,
+    variant: ChipVariant,
     calib: CalibData,
+    compensation: Compensation,
     // TODO remove ? as it may not reflect the state of the device
     tph_sett: TphSett,
     // TODO remove ? as it may not reflect the state of the device
@@ -419,15 +792,12 @@ pub struct Bme680<I2C, D> {
     power_mode: PowerMode,
 }
 
-fn boundary_check<I2C>(
+fn boundary_check<R, W>(
     value: Option<u8>,
     value_name: &'static str,
     min: u8,
     max: u8,
-) -> Result<u8, <I2C as Read>::Error, <I2C as Write>::Error>
-where
-    I2C: Read + Write,
-{
+) -> Result<u8, R, W> {
     let value = value.ok_or(Error::BoundaryCheckFailure(value_name))?;
 
     if value < min {
@@ -444,46 +814,904 @@ where
     Ok(value)
 }
 
-impl<I2C, D> Bme680<I2C, D>
+/// Builds the calibration data from the coefficient registers and the
+/// heater resistance / range switching error registers.
+fn calib_data_from_regs(
+    coeff_array: &[u8; BME680_COEFF_ADDR1_LEN + BME680_COEFF_ADDR2_LEN],
+    res_heat_range: u8,
+    res_heat_val: u8,
+    range_sw_err: u8,
+) -> CalibData {
+    CalibData {
+        par_t1: ((coeff_array[34usize] as i32) << 8i32 | coeff_array[33usize] as i32) as u16,
+        par_t2: ((coeff_array[2usize] as i32) << 8i32 | coeff_array[1usize] as i32) as i16,
+        par_t3: coeff_array[3usize] as i8,
+        par_p1: ((coeff_array[6usize] as i32) << 8i32 | coeff_array[5usize] as i32) as u16,
+        par_p2: ((coeff_array[8usize] as i32) << 8i32 | coeff_array[7usize] as i32) as i16,
+        par_p3: coeff_array[9usize] as i8,
+        par_p4: ((coeff_array[12usize] as i32) << 8i32 | coeff_array[11usize] as i32) as i16,
+        par_p5: ((coeff_array[14usize] as i32) << 8i32 | coeff_array[13usize] as i32) as i16,
+        par_p6: coeff_array[16usize] as i8,
+        par_p7: coeff_array[15usize] as i8,
+        par_p8: ((coeff_array[20usize] as i32) << 8i32 | coeff_array[19usize] as i32) as i16,
+        par_p9: ((coeff_array[22usize] as i32) << 8i32 | coeff_array[21usize] as i32) as i16,
+        par_p10: coeff_array[23usize],
+        par_h1: ((coeff_array[27usize] as i32) << 4i32 | coeff_array[26usize] as i32 & 0xfi32)
+            as u16,
+        par_h2: ((coeff_array[25usize] as i32) << 4i32 | coeff_array[26usize] as i32 >> 4i32)
+            as u16,
+        par_h3: coeff_array[28usize] as i8,
+        par_h4: coeff_array[29usize] as i8,
+        par_h5: coeff_array[30usize] as i8,
+        par_h6: coeff_array[31usize],
+        par_h7: coeff_array[32usize] as i8,
+        par_gh1: coeff_array[37usize] as i8,
+        par_gh2: ((coeff_array[36usize] as i32) << 8i32 | coeff_array[35usize] as i32) as i16,
+        par_gh3: coeff_array[38usize] as i8,
+        res_heat_range: (res_heat_range & 0x30) / 16,
+        res_heat_val: res_heat_val as i8,
+        range_sw_err: (range_sw_err & BME680_RSERROR_MSK) / 16,
+    }
+}
+
+/// Fails with `Error::InvalidCalibration` if the calibration data is implausible
+fn validate_calib<R, W>(calib: CalibData) -> Result<CalibData, R, W> {
+    match calib.validate() {
+        Ok(()) => Ok(calib),
+        Err(parameter) => {
+            error!("Implausible calibration data: {}", parameter);
+            Err(Error::InvalidCalibration(parameter))
+        }
+    }
+}
+
+/// Register address and value pairs, of which only the first `usize` are used
+type RegBuffer = ([(u8, u8); BME680_TMP_BUFFER_LENGTH / 2], usize);
+
+/// Computes the register values for the desired settings, based on the current
+/// content of the configuration registers `0x70..=0x75`.
+/// The heater set-point is included if gas measurement settings are desired, so all
+/// registers can be written in a single burst.
+fn sensor_settings_regs<R, W>(
+    calib: &CalibData,
+    variant: ChipVariant,
+    desired_settings: DesiredSensorSettings,
+    tph_sett: &TphSett,
+    gas_sett: &GasSett,
+    conf_regs: &[u8; BME680_REG_BUFFER_LENGTH],
+) -> Result<RegBuffer, R, W> {
+    let mut reg: [(u8, u8); BME680_TMP_BUFFER_LENGTH / 2] = [(0, 0); BME680_TMP_BUFFER_LENGTH / 2];
+    let conf_reg = |addr: u8| conf_regs[(addr - BME680_CONF_HEAT_CTRL_ADDR) as usize];
+
+    let mut element_index = 0;
+    if desired_settings.contains(DesiredSensorSettings::GAS_MEAS_SEL) {
+        debug!("GAS_MEAS_SEL: true");
+        boundary_check(Some(gas_sett.nb_conv), "GasSett.nb_conv", 0, 9)?;
+        for gas_reg in gas_config_regs(calib, gas_sett).iter() {
+            reg[element_index] = *gas_reg;
+            element_index += 1;
+        }
+    }
+
+    // Selecting the filter
+    if desired_settings.contains(DesiredSensorSettings::FILTER_SEL) {
+        let mut data = conf_reg(BME680_CONF_ODR_FILT_ADDR);
+
+        debug!("FILTER_SEL: true");
+        data = (data as i32 & !0x1ci32
+            | (tph_sett.filter.unwrap_or(IIRFilterSize::Size0) as i32) << 2i32 & 0x1ci32)
+            as u8;
+        reg[element_index] = (BME680_CONF_ODR_FILT_ADDR, data);
+        element_index += 1;
+    }
+
+    if desired_settings.contains(DesiredSensorSettings::HCNTRL_SEL) {
+        debug!("HCNTRL_SEL: true");
+        let gas_sett_heatr_ctrl =
+            boundary_check(gas_sett.heatr_ctrl, "GasSett.heatr_ctrl", 0x0u8, 0x8u8)?;
+        let mut data = conf_reg(BME680_CONF_HEAT_CTRL_ADDR);
+        data = (data as i32 & !0x8i32 | gas_sett_heatr_ctrl as i32 & 0x8) as u8;
+        reg[element_index] = (BME680_CONF_HEAT_CTRL_ADDR, data);
+        element_index += 1;
+    }
+
+    // Selecting heater T,P oversampling for the sensor
+    if desired_settings.contains(DesiredSensorSettings::OST_SEL | DesiredSensorSettings::OSP_SEL) {
+        let mut data = conf_reg(BME680_CONF_T_P_MODE_ADDR);
+
+        if desired_settings.contains(DesiredSensorSettings::OST_SEL) {
+            debug!("OST_SEL: true");
+            let tph_sett_os_temp =
+                boundary_check(tph_sett.os_temp.map(|x| x as u8), "TphSett.os_temp", 0, 5)?;
+            data = (data as i32 & !0xe0i32 | (tph_sett_os_temp as i32) << 5i32 & 0xe0i32) as u8;
+        }
+
+        if desired_settings.contains(DesiredSensorSettings::OSP_SEL) {
+            debug!("OSP_SEL: true");
+            let tph_sett_os_pres = tph_sett.os_temp.expect("OS TEMP");
+            data = (data as i32 & !0x1ci32 | (tph_sett_os_pres as i32) << 2i32 & 0x1ci32) as u8;
+        }
+        reg[element_index] = (BME680_CONF_T_P_MODE_ADDR, data);
+        element_index += 1;
+    }
+
+    // Selecting humidity oversampling for the sensor
+    if desired_settings.contains(DesiredSensorSettings::OSH_SEL) {
+        debug!("OSH_SEL: true");
+        let tph_sett_os_hum =
+            boundary_check(tph_sett.os_hum.map(|x| x as u8), "TphSett.os_hum", 0, 5)?;
+        let mut data = conf_reg(BME680_CONF_OS_H_ADDR);
+        data = (data as i32 & !0x7i32 | tph_sett_os_hum as i32 & 0x7i32) as u8;
+        reg[element_index] = (BME680_CONF_OS_H_ADDR, data);
+        element_index += 1;
+    }
+
+    // Selecting the runGas and NB conversion settings for the sensor
+    if desired_settings
+        .contains(DesiredSensorSettings::RUN_GAS_SEL | DesiredSensorSettings::NBCONV_SEL)
+    {
+        let mut data = conf_reg(BME680_CONF_ODR_RUN_GAS_NBC_ADDR);
+
+        if desired_settings.contains(DesiredSensorSettings::RUN_GAS_SEL) {
+            debug!("RUN_GAS_SEL: true");
+            data &= !BME680_RUN_GAS_MSK;
+            if gas_sett.run_gas_measurement {
+                data |= variant.run_gas_value();
+            }
+        }
+
+        if desired_settings.contains(DesiredSensorSettings::NBCONV_SEL) {
+            debug!("NBCONV_SEL: true");
+            let gas_sett_nb_conv = boundary_check(Some(gas_sett.nb_conv), "GasSett.nb_conv", 0, 9)?;
+            data = (data as i32 & !0xfi32 | gas_sett_nb_conv as i32 & 0xfi32) as u8;
+        }
+
+        reg[element_index] = (BME680_CONF_ODR_RUN_GAS_NBC_ADDR, data);
+        element_index += 1;
+    }
+
+    Ok((reg, element_index))
+}
+
+/// Decodes the desired settings from the configuration registers `0x70..=0x75`.
+fn sensor_settings_from_regs(
+    variant: ChipVariant,
+    desired_settings: DesiredSensorSettings,
+    data_array: &[u8; BME680_REG_BUFFER_LENGTH],
+    sensor_settings: &mut SensorSettings,
+) {
+    if desired_settings.contains(DesiredSensorSettings::FILTER_SEL) {
+        sensor_settings.tph_sett.filter = Some(IIRFilterSize::from_u8(
+            ((data_array[5usize] as i32 & 0x1ci32) >> 2i32) as u8,
+        ));
+    }
+
+    if desired_settings.contains(DesiredSensorSettings::OST_SEL | DesiredSensorSettings::OSP_SEL) {
+        let os_temp: u8 = ((data_array[4usize] as i32 & 0xe0i32) >> 5i32) as u8;
+        let os_pres: u8 = ((data_array[4usize] as i32 & 0x1ci32) >> 2i32) as u8;
+        sensor_settings.tph_sett.os_temp = Some(OversamplingSetting::from_u8(os_temp));
+        sensor_settings.tph_sett.os_pres = Some(OversamplingSetting::from_u8(os_pres));
+    }
+
+    if desired_settings.contains(DesiredSensorSettings::OSH_SEL) {
+        let os_hum: u8 = (data_array[2usize] as i32 & 0x7i32) as u8;
+        sensor_settings.tph_sett.os_hum = Some(OversamplingSetting::from_u8(os_hum));
+    }
+
+    if desired_settings.contains(DesiredSensorSettings::HCNTRL_SEL) {
+        sensor_settings.gas_sett.heatr_ctrl = Some((data_array[0usize] as i32 & 0x8i32) as u8);
+    }
+
+    if desired_settings
+        .contains(DesiredSensorSettings::RUN_GAS_SEL | DesiredSensorSettings::NBCONV_SEL)
+    {
+        sensor_settings.gas_sett.nb_conv = (data_array[1usize] as i32 & 0xfi32) as u8;
+        sensor_settings.gas_sett.run_gas_measurement =
+            data_array[1usize] & BME680_RUN_GAS_MSK == variant.run_gas_value();
+    }
+}
+
+/// Decodes the heater set-points from the `res_heat_x` and `gas_wait_x` registers
+///
+/// `heatr_temp` and `heatr_dur` are those of the set-point selected by `ctrl_gas_1`.
+fn gas_config_from_regs(
+    calib: &CalibData,
+    ambient_temperature: i8,
+    ctrl_gas_1: u8,
+    res_heat: &[u8; BME680_HEATER_STEPS],
+    gas_wait: &[u8; BME680_HEATER_STEPS],
+) -> GasSett {
+    let mut gas_sett = GasSett {
+        ambient_temperature,
+        ..Default::default()
+    };
+    for (set_point, (res_heat, gas_wait)) in gas_sett
+        .heater_profile
+        .iter_mut()
+        .zip(res_heat.iter().zip(gas_wait.iter()))
+    {
+        let (temperature, temperature_error) =
+            Calc::calc_heater_temp(calib, ambient_temperature, *res_heat);
+        let (duration, duration_error) = Calc::calc_heater_duration(*gas_wait);
+        *set_point = HeaterSetPoint {
+            temperature,
+            temperature_error,
+            duration,
+            duration_error,
+        };
+    }
+
+    // nb_conv may exceed the number of set-points after a failed write
+    let selected = gas_sett
+        .heater_profile
+        .get((ctrl_gas_1 & BME680_NBCONV_MSK) as usize)
+        .copied();
+    gas_sett.heatr_temp = selected.map(|set_point| set_point.temperature);
+    gas_sett.heatr_dur = selected.map(|set_point| set_point.duration);
+    gas_sett
+}
+
+/// Heater resistance and gas wait register values of the set-point selected by `nb_conv`
+fn gas_config_regs(calib: &CalibData, gas_sett: &GasSett) -> [(u8, u8); 2] {
+    // TODO check whether unwrap_or changes behaviour
+    heater_step_regs(
+        calib,
+        gas_sett.nb_conv,
+        gas_sett.ambient_temperature,
+        &HeaterStep::new(
+            gas_sett.heatr_temp.unwrap_or(0),
+            gas_sett.heatr_dur.unwrap_or_else(|| Duration::from_secs(0)),
+        ),
+    )
+}
+
+/// Heater resistance and gas wait register values of a single set-point
+fn heater_step_regs(
+    calib: &CalibData,
+    index: u8,
+    ambient_temperature: i8,
+    step: &HeaterStep,
+) -> [(u8, u8); 2] {
+    [
+        (
+            BME680_RES_HEAT0_ADDR + index,
+            Calc::calc_heater_res(calib, ambient_temperature, step.temperature),
+        ),
+        (
+            BME680_GAS_WAIT0_ADDR + index,
+            Calc::calc_heater_dur(step.duration),
+        ),
+    ]
+}
+
+/// Heater resistance and gas wait register values of a heater profile, starting at set-point 0
+fn heater_profile_regs<R, W>(
+    calib: &CalibData,
+    ambient_temperature: i8,
+    steps: &[HeaterStep],
+) -> Result<RegBuffer, R, W> {
+    if steps.is_empty() || steps.len() > BME680_HEATER_STEPS {
+        return Err(Error::InvalidLength);
+    }
+
+    let mut reg: [(u8, u8); BME680_TMP_BUFFER_LENGTH / 2] = [(0, 0); BME680_TMP_BUFFER_LENGTH / 2];
+    let mut element_index = 0;
+    for (index, step) in steps.iter().enumerate() {
+        for step_reg in heater_step_regs(calib, index as u8, ambient_temperature, step).iter() {
+            reg[element_index] = *step_reg;
+            element_index += 1;
+        }
+    }
+    Ok((reg, element_index))
+}
+
+/// Registers of a BME688 sequential mode heater profile, including `ctrl_gas_1` enabling
+/// gas measurements across all steps
+fn sequential_profile_regs<R, W>(
+    calib: &CalibData,
+    variant: ChipVariant,
+    ctrl_gas_1: u8,
+    ambient_temperature: i8,
+    steps: &[HeaterStep],
+) -> Result<RegBuffer, R, W> {
+    let (mut reg, element_index) = heater_profile_regs(calib, ambient_temperature, steps)?;
+    reg[element_index] = (
+        BME680_CONF_ODR_RUN_GAS_NBC_ADDR,
+        ctrl_gas_1 & !(BME680_RUN_GAS_MSK | BME680_NBCONV_MSK)
+            | variant.run_gas_value()
+            | steps.len() as u8,
+    );
+    Ok((reg, element_index + 1))
+}
+
+/// `ctrl_gas_1` value selecting the heater set-point `nb_conv`
+fn heater_step_select_reg<R, W>(ctrl_gas_1: u8, nb_conv: u8) -> Result<(u8, u8), R, W> {
+    let nb_conv = boundary_check(Some(nb_conv), "nb_conv", 0, 9)?;
+    Ok((
+        BME680_CONF_ODR_RUN_GAS_NBC_ADDR,
+        ctrl_gas_1 & !BME680_NBCONV_MSK | nb_conv,
+    ))
+}
+
+/// `gas_wait_shared` value for the given heating duration, in steps of 0.477 ms
+fn calc_heater_dur_shared(duration: Duration) -> u8 {
+    let ms = duration.as_millis();
+    if ms >= 0x783 {
+        // Max duration
+        return 0xff;
+    }
+
+    let mut dur = ms * 1000 / 477;
+    let mut factor = 0u8;
+    while dur > 0x3f {
+        dur >>= 2;
+        factor += 1;
+    }
+    dur as u8 + factor * 64
+}
+
+/// Registers of a BME688 parallel mode heater profile, including `ctrl_gas_1` enabling
+/// gas measurements across all steps
+fn parallel_profile_regs<R, W>(
+    calib: &CalibData,
+    variant: ChipVariant,
+    ctrl_gas_1: u8,
+    ambient_temperature: i8,
+    shared_duration: Duration,
+    steps: &[ParallelHeaterStep],
+) -> Result<RegBuffer, R, W> {
+    if steps.is_empty() || steps.len() > BME680_HEATER_STEPS {
+        return Err(Error::InvalidLength);
+    }
+
+    let mut reg: [(u8, u8); BME680_TMP_BUFFER_LENGTH / 2] = [(0, 0); BME680_TMP_BUFFER_LENGTH / 2];
+    let mut element_index = 0;
+    for (index, step) in steps.iter().enumerate() {
+        reg[element_index] = (
+            BME680_RES_HEAT0_ADDR + index as u8,
+            Calc::calc_heater_res(calib, ambient_temperature, step.temperature),
+        );
+        reg[element_index + 1] = (BME680_GAS_WAIT0_ADDR + index as u8, step.multiplier);
+        element_index += 2;
+    }
+    reg[element_index] = (
+        BME688_GAS_WAIT_SHARED_ADDR,
+        calc_heater_dur_shared(shared_duration),
+    );
+    reg[element_index + 1] = (
+        BME680_CONF_ODR_RUN_GAS_NBC_ADDR,
+        ctrl_gas_1 & !(BME680_RUN_GAS_MSK | BME680_NBCONV_MSK)
+            | variant.run_gas_value()
+            | steps.len() as u8,
+    );
+    Ok((reg, element_index + 2))
+}
+
+/// Decodes all three fields, keeping those with new data ordered by measurement index
+fn field_data_set_from_regs(
+    buff: &[u8; BME680_FIELD_LENGTH * BME688_FIELDS],
+    calib: &CalibData,
+    variant: ChipVariant,
+    compensation: Compensation,
+    tph_sett: &TphSett,
+) -> result::Result<FieldDataSet, CompensationError> {
+    let mut set = FieldDataSet::default();
+    for field in buff.chunks(BME680_FIELD_LENGTH) {
+        let mut field_buff = [0; BME680_FIELD_LENGTH];
+        field_buff.copy_from_slice(field);
+        let data = field_data_from_regs(&field_buff, calib, variant, compensation, tph_sett)?;
+        if data.status & BME680_NEW_DATA_MSK != 0 {
+            set.fields[set.len] = data;
+            set.len += 1;
+        }
+    }
+
+    // The measurement index wraps around, the fields are at most a few measurements apart
+    if let Some(first) = set.fields[..set.len].first().map(|data| data.meas_index) {
+        set.fields[..set.len]
+            .sort_unstable_by_key(|data| data.meas_index.wrapping_sub(first) as i8);
+    }
+    Ok(set)
+}
+
+/// Duration of a full measurement cycle, including the heating duration if gas
+/// measurements are enabled.
+fn profile_dur(sensor_settings: &SensorSettings) -> Duration {
+    let os_to_meas_cycles: [u8; 6] = [0u8, 1u8, 2u8, 4u8, 8u8, 16u8];
+    // TODO check if the following unwrap_ors do not change behaviour
+    let mut meas_cycles = os_to_meas_cycles[sensor_settings
+        .tph_sett
+        .os_temp
+        .unwrap_or(OversamplingSetting::OSNone)
+        as usize] as u32;
+    meas_cycles = meas_cycles.wrapping_add(
+        os_to_meas_cycles[sensor_settings
+            .tph_sett
+            .os_pres
+            .unwrap_or(OversamplingSetting::OSNone) as usize] as u32,
+    );
+    meas_cycles = meas_cycles.wrapping_add(
+        os_to_meas_cycles[sensor_settings
+            .tph_sett
+            .os_hum
+            .unwrap_or(OversamplingSetting::OSNone) as usize] as u32,
+    );
+    let mut tph_dur = meas_cycles.wrapping_mul(1963u32);
+    tph_dur = tph_dur.wrapping_add(477u32.wrapping_mul(4u32));
+    tph_dur = tph_dur.wrapping_add(477u32.wrapping_mul(5u32));
+    tph_dur = tph_dur.wrapping_add(500u32);
+    tph_dur = tph_dur.wrapping_div(1000u32);
+    tph_dur = tph_dur.wrapping_add(1u32);
+    let mut duration = Duration::from_millis(tph_dur as u64);
+    if sensor_settings.gas_sett.run_gas_measurement {
+        duration += sensor_settings.gas_sett.heatr_dur.expect("Heatrdur");
+    }
+    duration
+}
+
+/// Blocks for the given duration, which may exceed the range of a single `delay_ms` call
+fn delay_for<D: DelayMs<u8>>(delay: &mut D, duration: Duration) {
+    let mut remaining = duration.as_millis();
+    while remaining > 0 {
+        let ms = remaining.min(u8::MAX as u128) as u8;
+        delay.delay_ms(ms);
+        remaining -= ms as u128;
+    }
+}
+
+/// Decodes the field data registers, compensating the values if new data is available.
+fn field_data_from_regs(
+    buff: &[u8; BME680_FIELD_LENGTH],
+    calib: &CalibData,
+    variant: ChipVariant,
+    compensation: Compensation,
+    tph_sett: &TphSett,
+) -> result::Result<FieldData, CompensationError> {
+    field_data_from_raw(
+        &raw_field_data_from_regs(buff, variant),
+        calib,
+        compensation,
+        tph_sett,
+    )
+}
+
+/// Compensates the raw values if new data is available
+///
+/// Only one of the temperature offsets of `tph_sett` is expected to be set, it is converted
+/// if the other one is used by the compensation.
+fn field_data_from_raw(
+    raw: &RawFieldData,
+    calib: &CalibData,
+    compensation: Compensation,
+    tph_sett: &TphSett,
+) -> result::Result<FieldData, CompensationError> {
+    if raw.status & BME680_NEW_DATA_MSK == 0 {
+        return Ok(FieldData {
+            status: raw.status,
+            gas_index: raw.gas_index,
+            meas_index: raw.meas_index,
+            ..Default::default()
+        });
+    }
+
+    let temperature_offset = || {
+        tph_sett.temperature_offset.or_else(|| {
+            tph_sett
+                .temperature_offset_centi
+                .map(|offset| offset as f32 / 100.0)
+        })
+    };
+    match compensation {
+        Compensation::Integer => compensate(calib, raw, temperature_offset()),
+        Compensation::Integer32 => {
+            let temperature_offset = match tph_sett.temperature_offset_centi {
+                Some(offset) => offset,
+                // Truncated like the conversion of `Calc::calc_temperature`
+                None => tph_sett
+                    .temperature_offset
+                    .map_or(0, |offset| (offset * 100.0) as i16),
+            };
+            compensate_i32(calib, raw, temperature_offset)
+        }
+        Compensation::Float => compensate_float(calib, raw, temperature_offset()),
+    }
+}
+
+/// Decodes the field data registers without compensating the values
+fn raw_field_data_from_regs(
+    buff: &[u8; BME680_FIELD_LENGTH],
+    variant: ChipVariant,
+) -> RawFieldData {
+    // gas_r_lsb/gas_r_msb at 0x2a/0x2b on the BME680 and 0x2c/0x2d on the BME688
+    let gas_regs = match variant {
+        ChipVariant::Bme680 => &buff[13..15],
+        ChipVariant::Bme688 => &buff[15..17],
+    };
+
+    let raw = RawFieldData {
+        status: buff[0] & BME680_NEW_DATA_MSK
+            | gas_regs[1] & BME680_GASM_VALID_MSK
+            | gas_regs[1] & BME680_HEAT_STAB_MSK,
+        gas_index: buff[0] & BME680_GAS_INDEX_MSK,
+        meas_index: buff[1],
+        adc_temp: (buff[5] as u32).wrapping_mul(4096)
+            | (buff[6] as u32).wrapping_mul(16)
+            | (buff[7] as u32).wrapping_div(16),
+        adc_pres: (buff[2] as u32).wrapping_mul(4096)
+            | (buff[3] as u32).wrapping_mul(16)
+            | (buff[4] as u32).wrapping_div(16),
+        adc_hum: ((buff[8] as u32).wrapping_mul(256) | buff[9] as u32) as u16,
+        adc_gas_res: ((gas_regs[0] as u32).wrapping_mul(4) | (gas_regs[1] as u32).wrapping_div(64))
+            as u16,
+        gas_range: gas_regs[1] & BME680_GAS_RANGE_MSK,
+        variant,
+    };
+    debug!(
+        "adc_temp: {} adc_pres: {} adc_hum: {} adc_gas_res: {}",
+        raw.adc_temp, raw.adc_pres, raw.adc_hum, raw.adc_gas_res
+    );
+    raw
+}
+
+/// Compensates raw sensor values using the integer formulas
+///
+/// This is what `get_sensor_data` does with the default [`Compensation::Integer`], it
+/// allows raw values recorded via `get_raw_sensor_data` to be compensated later on, e.g.
+/// on a server.
+///
+/// # Arguments
+///
+/// * `calib` - Calibration data of the sensor the values were read from, see `calib_data`
+/// * `temperature_offset` - Temperature offset in degree celsius, e.g. 4, -8, 1.25
+///
+/// Fails if the raw values are out of range or overflow the formulas.
+pub fn compensate(
+    calib: &CalibData,
+    raw: &RawFieldData,
+    temperature_offset: Option<f32>,
+) -> result::Result<FieldData, CompensationError> {
+    let (temperature, t_fine) = Calc::calc_temperature(calib, raw.adc_temp, temperature_offset)?;
+    Ok(FieldData {
+        status: raw.status,
+        gas_index: raw.gas_index,
+        meas_index: raw.meas_index,
+        temperature,
+        pressure: Calc::calc_pressure(calib, t_fine, raw.adc_pres)?,
+        humidity: Calc::calc_humidity(calib, t_fine, raw.adc_hum)?,
+        gas_resistance: match raw.variant {
+            ChipVariant::Bme680 => {
+                Calc::calc_gas_resistance(calib, raw.adc_gas_res, raw.gas_range)?
+            }
+            ChipVariant::Bme688 => Calc::calc_gas_resistance_high(raw.adc_gas_res, raw.gas_range)?,
+        },
+    })
+}
+
+/// Compensates raw sensor values using only 32-bit integer arithmetic
+///
+/// Gives the same results as [`compensate`], but avoids the 64-bit arithmetic and the
+/// floating-point temperature offset, which are slow on targets such as the Cortex-M0.
+/// The driver uses it with [`Compensation::Integer32`].
+///
+/// # Arguments
+///
+/// * `calib` - Calibration data of the sensor the values were read from, see `calib_data`
+/// * `temperature_offset` - Temperature offset in 0.01 degree celsius, e.g. 125 for 1.25 °C
+pub fn compensate_i32(
+    calib: &CalibData,
+    raw: &RawFieldData,
+    temperature_offset: i16,
+) -> result::Result<FieldData, CompensationError> {
+    let (temperature, t_fine) =
+        Calc::calc_temperature_i32(calib, raw.adc_temp, temperature_offset)?;
+    Ok(FieldData {
+        status: raw.status,
+        gas_index: raw.gas_index,
+        meas_index: raw.meas_index,
+        temperature,
+        // Pressure and humidity are computed using 32-bit arithmetic only
+        pressure: Calc::calc_pressure(calib, t_fine, raw.adc_pres)?,
+        humidity: Calc::calc_humidity(calib, t_fine, raw.adc_hum)?,
+        gas_resistance: match raw.variant {
+            ChipVariant::Bme680 => {
+                Calc::calc_gas_resistance_i32(calib, raw.adc_gas_res, raw.gas_range)?
+            }
+            ChipVariant::Bme688 => Calc::calc_gas_resistance_high(raw.adc_gas_res, raw.gas_range)?,
+        },
+    })
+}
+
+/// Compensates raw sensor values using the floating-point formulas, see [`compensate`]
+pub fn compensate_float(
+    calib: &CalibData,
+    raw: &RawFieldData,
+    temperature_offset: Option<f32>,
+) -> result::Result<FieldData, CompensationError> {
+    let (temperature, t_fine) =
+        Calc::calc_temperature_float(calib, raw.adc_temp, temperature_offset)?;
+    let gas_resistance = match raw.variant {
+        ChipVariant::Bme680 => {
+            Calc::calc_gas_resistance_float(calib, raw.adc_gas_res, raw.gas_range)?
+        }
+        ChipVariant::Bme688 => {
+            Calc::calc_gas_resistance_high_float(raw.adc_gas_res, raw.gas_range)?
+        }
+    };
+    // Rounded to the resolution of the integer formulas
+    Ok(FieldData {
+        status: raw.status,
+        gas_index: raw.gas_index,
+        meas_index: raw.meas_index,
+        temperature: round(temperature * 100.0) as i16,
+        pressure: round(Calc::calc_pressure_float(calib, t_fine, raw.adc_pres)?) as u32,
+        humidity: round(Calc::calc_humidity_float(calib, t_fine, raw.adc_hum)? * 1000.0) as u32,
+        gas_resistance: round(gas_resistance) as u32,
+    })
+}
+
+/// Rounds to the nearest integer, `f32::round` is not available without std
+fn round(value: f32) -> i64 {
+    if value < 0.0 {
+        (value - 0.5) as i64
+    } else {
+        (value + 0.5) as i64
+    }
+}
+
+impl<I2C, D> Bme680<I2cInterface<I2C>, D>
 where
     D: DelayMs<u8>,
-    I2C: Read + Write,
+    I2C: WriteRead + Write,
 {
-    pub fn soft_reset(
-        i2c: &mut I2C,
+    /// Initializes the sensor connected via I²C at the given address
+    pub fn init(
+        i2c: I2C,
         delay: &mut D,
         dev_id: I2CAddress,
-    ) -> Result<(), <I2C as Read>::Error, <I2C as Write>::Error> {
-        let tmp_buff: [u8; 2] = [BME680_SOFT_RESET_ADDR, BME680_SOFT_RESET_CMD];
+    ) -> Result<Bme680<I2cInterface<I2C>, D>, <I2C as WriteRead>::Error, <I2C as Write>::Error>
+    {
+        Bme680::init_with_interface(I2cInterface::new(i2c, dev_id), delay)
+    }
 
-        i2c.write(dev_id.addr(), &tmp_buff)
-            .map_err(Error::I2CWrite)?;
+    /// Initializes the sensor using stored calibration data instead of reading it
+    ///
+    /// See [`init_with_interface_and_calib`](Bme680::init_with_interface_and_calib).
+    pub fn init_with_calib(
+        i2c: I2C,
+        delay: &mut D,
+        dev_id: I2CAddress,
+        calib: CalibData,
+    ) -> Result<Self, <I2C as WriteRead>::Error, <I2C as Write>::Error> {
+        Bme680::init_with_interface_and_calib(I2cInterface::new(i2c, dev_id), delay, calib)
+    }
+
+    /// Initializes the sensor at whichever standard address it responds
+    ///
+    /// The primary address is tried first, use [`address`](Self::address) to find out
+    /// where the sensor was found.
+    pub fn init_detect(
+        mut i2c: I2C,
+        delay: &mut D,
+    ) -> Result<Self, <I2C as WriteRead>::Error, <I2C as Write>::Error> {
+        let dev_id = scan(&mut i2c).next().ok_or(Error::DeviceNotFound)?;
+        debug!("Detected sensor at {:#x}", dev_id.addr());
+        Bme680::init(i2c, delay, dev_id)
+    }
+
+    /// I²C address of the sensor
+    pub fn address(&self) -> I2CAddress {
+        self.interface.address()
+    }
+
+    /// Rebuilds the driver from a snapshot without accessing the sensor
+    ///
+    /// The sensor is addressed at the address stored in the snapshot. Fails with
+    /// [`SnapshotError::MissingAddress`] for snapshots taken via other interfaces, which are
+    /// resumed via [`resume_with_interface`](Self::resume_with_interface).
+    pub fn resume(i2c: I2C, snapshot: &Snapshot) -> result::Result<Self, SnapshotError> {
+        let dev_id = snapshot.address.ok_or(SnapshotError::MissingAddress)?;
+        Ok(Bme680::resume_with_interface(
+            I2cInterface::new(i2c, dev_id),
+            snapshot,
+        ))
+    }
+
+    /// Puts the sensor to sleep and returns the I²C bus
+    pub fn release(
+        self,
+        delay: &mut D,
+    ) -> Result<I2C, <I2C as WriteRead>::Error, <I2C as Write>::Error> {
+        self.release_interface(delay).map(I2cInterface::release)
+    }
+}
+
+impl<'a, I2C, D> Bme680<I2cInterface<I2cRef<'a, I2C>>, D>
+where
+    D: DelayMs<u8>,
+    I2C: WriteRead + Write,
+{
+    /// Initializes the sensor connected via I²C at the given address, borrowing the bus
+    ///
+    /// The bus can be used for other devices again once the driver is dropped or released.
+    pub fn init_borrowed(
+        i2c: &'a mut I2C,
+        delay: &mut D,
+        dev_id: I2CAddress,
+    ) -> Result<Self, <I2C as WriteRead>::Error, <I2C as Write>::Error> {
+        Bme680::init(I2cRef(i2c), delay, dev_id)
+    }
+}
+
+impl<SPI, CS, D, E, PinE> Bme680<SpiInterface<SPI, CS>, D>
+where
+    D: DelayMs<u8>,
+    SPI: spi::Transfer<u8, Error = E> + spi::Write<u8, Error = E>,
+    CS: OutputPin<Error = PinE>,
+{
+    /// Initializes the sensor connected via 4-wire SPI
+    pub fn init_spi(
+        spi: SPI,
+        cs: CS,
+        delay: &mut D,
+    ) -> Result<Self, SpiError<E, PinE>, SpiError<E, PinE>> {
+        Bme680::init_with_interface(SpiInterface::new(spi, cs), delay)
+    }
+
+    /// Initializes the sensor connected via 3-wire SPI
+    ///
+    /// The sensor starts in 4-wire mode after a reset, 3-wire mode is enabled before
+    /// reading any data.
+    pub fn init_spi_3wire(
+        spi: SPI,
+        cs: CS,
+        delay: &mut D,
+    ) -> Result<Self, SpiError<E, PinE>, SpiError<E, PinE>> {
+        let mut interface = SpiInterface::new(spi, cs);
+        Bme680::soft_reset(&mut interface, delay)?;
+        interface.write_registers(&[(BME680_CONF_ODR_FILT_ADDR, BME680_SPI_3W_EN_MSK)])?;
+        Bme680::from_reset_interface(interface, delay, None, BME680_CALIB_READ_ATTEMPTS)
+    }
+
+    /// Puts the sensor to sleep and returns the SPI bus and chip select pin
+    pub fn release(self, delay: &mut D) -> Result<(SPI, CS), SpiError<E, PinE>, SpiError<E, PinE>> {
+        self.release_interface(delay).map(SpiInterface::release)
+    }
+}
+
+impl<IF, D> Bme680<IF, D>
+where
+    D: DelayMs<u8>,
+    IF: Interface,
+{
+    pub fn soft_reset(
+        interface: &mut IF,
+        delay: &mut D,
+    ) -> Result<(), IF::ReadError, IF::WriteError> {
+        interface.write_registers(&[(BME680_SOFT_RESET_ADDR, BME680_SOFT_RESET_CMD)])?;
 
         delay.delay_ms(BME680_RESET_PERIOD);
         Ok(())
     }
 
-    pub fn init(
-        mut i2c: I2C,
+    /// Initializes the sensor connected via the given interface
+    pub fn init_with_interface(
+        interface: IF,
         delay: &mut D,
-        dev_id: I2CAddress,
-    ) -> Result<Bme680<I2C, D>, <I2C as Read>::Error, <I2C as Write>::Error> {
-        Bme680::soft_reset(&mut i2c, delay, dev_id)?;
+    ) -> Result<Bme680<IF, D>, IF::ReadError, IF::WriteError> {
+        Bme680::init_with_calib_attempts(interface, delay, BME680_CALIB_READ_ATTEMPTS)
+    }
+
+    /// Initializes the sensor, reading the calibration data up to `attempts` times until
+    /// it is plausible
+    ///
+    /// Fails with [`Error::InvalidCalibration`] naming the implausible parameter or register
+    /// block of the last read, see [`CalibData::validate`].
+    pub fn init_with_calib_attempts(
+        mut interface: IF,
+        delay: &mut D,
+        attempts: u8,
+    ) -> Result<Bme680<IF, D>, IF::ReadError, IF::WriteError> {
+        Bme680::soft_reset(&mut interface, delay)?;
+        Bme680::from_reset_interface(interface, delay, None, attempts)
+    }
+
+    /// Initializes the sensor using stored calibration data instead of reading it
+    ///
+    /// The calibration data of a sensor never changes, so it can be read once using
+    /// [`calib_data`](Self::calib_data), stored e.g. via [`CalibData::to_bytes`] and be
+    /// passed in after waking from deep sleep. It is checked using [`CalibData::validate`].
+    pub fn init_with_interface_and_calib(
+        mut interface: IF,
+        delay: &mut D,
+        calib: CalibData,
+    ) -> Result<Bme680<IF, D>, IF::ReadError, IF::WriteError> {
+        Bme680::soft_reset(&mut interface, delay)?;
+        Bme680::from_reset_interface(interface, delay, Some(calib), 1)
+    }
 
+    /// Rebuilds the driver from a snapshot without accessing the sensor
+    ///
+    /// Neither resets the sensor nor reads the chip id or calibration data, so the sensor
+    /// has to be the one the snapshot was taken of and must have stayed powered since.
+    pub fn resume_with_interface(interface: IF, snapshot: &Snapshot) -> Self {
+        Bme680 {
+            interface,
+            delay: PhantomData,
+            variant: snapshot.variant,
+            calib: snapshot.calib,
+            compensation: snapshot.compensation,
+            tph_sett: snapshot.tph_sett,
+            gas_sett: snapshot.gas_sett,
+            power_mode: snapshot.power_mode,
+        }
+    }
+
+    /// Captures the driver state, to be restored via
+    /// [`resume_with_interface`](Self::resume_with_interface)
+    pub fn snapshot(&self) -> Snapshot {
+        Snapshot {
+            address: self.interface.i2c_address(),
+            variant: self.variant,
+            calib: self.calib,
+            compensation: self.compensation,
+            tph_sett: self.tph_sett,
+            gas_sett: self.gas_sett,
+            power_mode: self.power_mode,
+        }
+    }
+
+    /// Variant of the sensor, read during initialization
+    pub fn chip_variant(&self) -> ChipVariant {
+        self.variant
+    }
+
+    /// Calibration data read during initialization
+    pub fn calib_data(&self) -> CalibData {
+        self.calib
+    }
+
+    /// Formulas used to compensate the sensor data
+    pub fn compensation(&self) -> Compensation {
+        self.compensation
+    }
+
+    /// Selects the formulas used to compensate the sensor data, integer by default
+    pub fn set_compensation(&mut self, compensation: Compensation) {
+        self.compensation = compensation;
+    }
+
+    /// Puts the sensor to sleep and returns the interface
+    pub fn release_interface(mut self, delay: &mut D) -> Result<IF, IF::ReadError, IF::WriteError> {
+        self.set_sensor_mode(delay, PowerMode::SleepMode)?;
+        Ok(self.interface)
+    }
+
+    /// Checks the chip id and reads the calibration data of a freshly reset sensor, unless given
+    fn from_reset_interface(
+        mut interface: IF,
+        delay: &mut D,
+        calib: Option<CalibData>,
+        attempts: u8,
+    ) -> Result<Bme680<IF, D>, IF::ReadError, IF::WriteError> {
         debug!("Reading chip id");
         /* Soft reset to restore it to default values*/
-        let chip_id = I2CUtil::read_byte::<I2C>(&mut i2c, dev_id.addr(), BME680_CHIP_ID_ADDR)?;
+        let chip_id = interface.read_register(BME680_CHIP_ID_ADDR)?;
         debug!("Chip id: {}", chip_id);
 
         if chip_id == BME680_CHIP_ID {
-            debug!("Reading calib data");
-            let calib = Bme680::<I2C, D>::get_calib_data::<I2C>(&mut i2c, dev_id)?;
+            let variant = ChipVariant::from(interface.read_register(BME680_VARIANT_ID_ADDR)?);
+            debug!("Chip variant: {:?}", variant);
+            let calib = match calib {
+                Some(calib) => validate_calib(calib)?,
+                None => Bme680::<IF, D>::read_calib_data(&mut interface, delay, attempts)?,
+            };
             debug!("Calib data {:?}", calib);
             let dev = Bme680 {
-                i2c,
+                interface,
                 delay: PhantomData,
-                dev_id,
+                variant,
                 calib,
+                compensation: Compensation::default(),
                 power_mode: PowerMode::ForcedMode,
                 tph_sett: Default::default(),
                 gas_sett: Default::default(),
@@ -496,149 +1724,193 @@ where
         }
     }
 
-    fn bme680_set_regs(
-        &mut self,
-        reg: &[(u8, u8)],
-    ) -> Result<(), <I2C as Read>::Error, <I2C as Write>::Error> {
-        if reg.is_empty() || reg.len() > (BME680_TMP_BUFFER_LENGTH / 2) as usize {
+    fn bme680_set_regs(&mut self, reg: &[(u8, u8)]) -> Result<(), IF::ReadError, IF::WriteError> {
+        if reg.is_empty() || reg.len() > BME680_TMP_BUFFER_LENGTH / 2 {
             return Err(Error::InvalidLength);
         }
 
-        for (reg_addr, reg_data) in reg {
-            let tmp_buff: [u8; 2] = [*reg_addr, *reg_data];
-            debug!(
-                "Setting register reg: {:?} tmp_buf: {:?}",
-                reg_addr, tmp_buff
-            );
-            self.i2c
-                .write(self.dev_id.addr(), &tmp_buff)
-                .map_err(Error::I2CWrite)?;
-        }
-
-        Ok(())
+        debug!("Setting registers {:?}", reg);
+        self.interface.write_registers(reg)
     }
 
     /// Set the settings to be used during the sensor measurements
+    ///
+    /// Fails with [`Error::DefinePwrMode`] if gas settings are given while the sensor is in
+    /// parallel or sequential mode, whose heater profile they would overwrite.
     pub fn set_sensor_settings(
         &mut self,
         delay: &mut D,
         settings: Settings,
-    ) -> Result<(), <I2C as Read>::Error, <I2C as Write>::Error> {
+    ) -> Result<(), IF::ReadError, IF::WriteError> {
         let (sensor_settings, desired_settings) = settings;
         let tph_sett = sensor_settings.tph_sett;
         let gas_sett = sensor_settings.gas_sett;
 
-        let mut reg: [(u8, u8); BME680_REG_BUFFER_LENGTH] = [(0, 0); BME680_REG_BUFFER_LENGTH];
-        let intended_power_mode = self.power_mode;
-
-        if desired_settings.contains(DesiredSensorSettings::GAS_MEAS_SEL) {
-            debug!("GAS_MEAS_SEL: true");
-            self.set_gas_config(gas_sett)?;
+        // The heater set-points hold the profile of parallel or sequential mode
+        if desired_settings.contains(DesiredSensorSettings::GAS_MEAS_SEL)
+            && matches!(
+                self.power_mode,
+                PowerMode::ParallelMode | PowerMode::SequentialMode
+            )
+        {
+            return Err(Error::DefinePwrMode);
         }
 
-        let power_mode = self.power_mode;
-        self.set_sensor_mode(delay, power_mode)?;
+        // Configuration registers may only be written in sleep mode
+        self.set_sensor_mode(delay, PowerMode::SleepMode)?;
 
-        let mut element_index = 0;
-        // Selecting the filter
-        if desired_settings.contains(DesiredSensorSettings::FILTER_SEL) {
-            let mut data =
-                I2CUtil::read_byte(&mut self.i2c, self.dev_id.addr(), BME680_CONF_ODR_FILT_ADDR)?;
+        let mut conf_regs: [u8; BME680_REG_BUFFER_LENGTH] = [0; BME680_REG_BUFFER_LENGTH];
+        self.interface
+            .read_registers(BME680_CONF_HEAT_CTRL_ADDR, &mut conf_regs)?;
 
-            debug!("FILTER_SEL: true");
-            data = (data as i32 & !0x1ci32
-                | (tph_sett.filter.unwrap_or(IIRFilterSize::Size0) as i32) << 2i32 & 0x1ci32)
-                as u8;
-            reg[element_index] = (BME680_CONF_ODR_FILT_ADDR, data);
-            element_index += 1;
+        let (reg, element_index) = sensor_settings_regs(
+            &self.calib,
+            self.variant,
+            desired_settings,
+            &tph_sett,
+            &gas_sett,
+            &conf_regs,
+        )?;
+        if element_index > 0 {
+            self.bme680_set_regs(&reg[0..element_index])?;
         }
 
-        if desired_settings.contains(DesiredSensorSettings::HCNTRL_SEL) {
-            debug!("HCNTRL_SEL: true");
-            let gas_sett_heatr_ctrl =
-                boundary_check::<I2C>(gas_sett.heatr_ctrl, "GasSett.heatr_ctrl", 0x0u8, 0x8u8)?;
-            let mut data = I2CUtil::read_byte(
-                &mut self.i2c,
-                self.dev_id.addr(),
-                BME680_CONF_HEAT_CTRL_ADDR,
-            )?;
-            data = (data as i32 & !0x8i32 | gas_sett_heatr_ctrl as i32 & 0x8) as u8;
-            reg[element_index] = (BME680_CONF_HEAT_CTRL_ADDR, data);
-            element_index += 1;
-        }
+        self.tph_sett = tph_sett;
+        self.gas_sett.record_settings(desired_settings, &gas_sett);
+        Ok(())
+    }
 
-        // Selecting heater T,P oversampling for the sensor
-        if desired_settings
-            .contains(DesiredSensorSettings::OST_SEL | DesiredSensorSettings::OSP_SEL)
-        {
-            let mut data =
-                I2CUtil::read_byte(&mut self.i2c, self.dev_id.addr(), BME680_CONF_T_P_MODE_ADDR)?;
-
-            if desired_settings.contains(DesiredSensorSettings::OST_SEL) {
-                debug!("OST_SEL: true");
-                let tph_sett_os_temp = boundary_check::<I2C>(
-                    tph_sett.os_temp.map(|x| x as u8),
-                    "TphSett.os_temp",
-                    0,
-                    5,
-                )?;
-                data = (data as i32 & !0xe0i32 | (tph_sett_os_temp as i32) << 5i32 & 0xe0i32) as u8;
-            }
+    /// Configures the heater set-points of a heater profile
+    ///
+    /// The steps are written to the set-points 0 to `steps.len() - 1`, at most
+    /// [`BME680_HEATER_STEPS`] steps are supported. The set-point used for the next gas
+    /// measurement is chosen with [`select_heater_step`](Self::select_heater_step).
+    ///
+    /// # Arguments
+    ///
+    /// * `ambient_temperature` - Ambient temperature in degree celsius used to compute the heater resistance
+    pub fn set_heater_profile(
+        &mut self,
+        delay: &mut D,
+        ambient_temperature: i8,
+        steps: &[HeaterStep],
+    ) -> Result<(), IF::ReadError, IF::WriteError> {
+        let (reg, element_index) = heater_profile_regs(&self.calib, ambient_temperature, steps)?;
 
-            if desired_settings.contains(DesiredSensorSettings::OSP_SEL) {
-                debug!("OSP_SEL: true");
-                let tph_sett_os_pres = tph_sett.os_temp.expect("OS TEMP");
-                data = (data as i32 & !0x1ci32 | (tph_sett_os_pres as i32) << 2i32 & 0x1ci32) as u8;
-            }
-            reg[element_index] = (BME680_CONF_T_P_MODE_ADDR, data);
-            element_index += 1;
-        }
+        self.set_sensor_mode(delay, PowerMode::SleepMode)?;
+        self.bme680_set_regs(&reg[0..element_index])?;
+        self.gas_sett.record_heater_profile(
+            ambient_temperature,
+            steps.iter().map(|step| (step.temperature, step.duration)),
+        );
+        // The selected set-point may have been reprogrammed
+        self.gas_sett.record_heater_step(self.gas_sett.nb_conv);
+        Ok(())
+    }
 
-        // Selecting humidity oversampling for the sensor
-        if desired_settings.contains(DesiredSensorSettings::OSH_SEL) {
-            debug!("OSH_SEL: true");
-            let tph_sett_os_hum =
-                boundary_check::<I2C>(tph_sett.os_hum.map(|x| x as u8), "TphSett.os_hum", 0, 5)?;
-            let mut data =
-                I2CUtil::read_byte(&mut self.i2c, self.dev_id.addr(), BME680_CONF_OS_H_ADDR)?;
-            data = (data as i32 & !0x7i32 | tph_sett_os_hum as i32 & 0x7i32) as u8;
-            reg[element_index] = (BME680_CONF_OS_H_ADDR, data);
-            element_index += 1;
-        }
+    /// Selects the heater set-point used by the next gas measurements
+    ///
+    /// Readings report the set-point used via [`FieldData::gas_index`].
+    pub fn select_heater_step(
+        &mut self,
+        delay: &mut D,
+        nb_conv: u8,
+    ) -> Result<(), IF::ReadError, IF::WriteError> {
+        self.set_sensor_mode(delay, PowerMode::SleepMode)?;
+
+        let ctrl_gas_1 = self
+            .interface
+            .read_register(BME680_CONF_ODR_RUN_GAS_NBC_ADDR)?;
+        let reg = heater_step_select_reg(ctrl_gas_1, nb_conv)?;
+        self.bme680_set_regs(&[reg])?;
+        self.gas_sett.record_heater_step(nb_conv);
+        Ok(())
+    }
 
-        // Selecting the runGas and NB conversion settings for the sensor
-        if desired_settings
-            .contains(DesiredSensorSettings::RUN_GAS_SEL | DesiredSensorSettings::NBCONV_SEL)
-        {
-            let mut data = I2CUtil::read_byte(
-                &mut self.i2c,
-                self.dev_id.addr(),
-                BME680_CONF_ODR_RUN_GAS_NBC_ADDR,
-            )?;
-
-            if desired_settings.contains(DesiredSensorSettings::RUN_GAS_SEL) {
-                debug!("RUN_GAS_SEL: true");
-                data = (data as i32 & !0x10i32
-                    | (gas_sett.run_gas_measurement as i32) << 4i32 & 0x10i32)
-                    as u8;
-            }
+    /// Configures the heater profile used in parallel mode, BME688 only
+    ///
+    /// Each step heats for its multiplier times the shared heating duration, gas
+    /// measurements are enabled for all steps. Start the measurements by setting
+    /// [`PowerMode::ParallelMode`] and read them with [`get_all_field_data`](Self::get_all_field_data).
+    ///
+    /// # Arguments
+    ///
+    /// * `ambient_temperature` - Ambient temperature in degree celsius used to compute the heater resistance
+    /// * `shared_duration` - Heating duration shared by all steps, in addition to the TPH measurement
+    pub fn set_parallel_heater_profile(
+        &mut self,
+        delay: &mut D,
+        ambient_temperature: i8,
+        shared_duration: Duration,
+        steps: &[ParallelHeaterStep],
+    ) -> Result<(), IF::ReadError, IF::WriteError> {
+        if !self.variant.supports(PowerMode::ParallelMode) {
+            return Err(Error::DefinePwrMode);
+        }
 
-            if desired_settings.contains(DesiredSensorSettings::NBCONV_SEL) {
-                debug!("NBCONV_SEL: true");
-                let gas_sett_nb_conv =
-                    boundary_check::<I2C>(Some(gas_sett.nb_conv), "GasSett.nb_conv", 0, 10)?;
-                data = (data as i32 & !0xfi32 | gas_sett_nb_conv as i32 & 0xfi32) as u8;
-            }
+        self.set_sensor_mode(delay, PowerMode::SleepMode)?;
+
+        let ctrl_gas_1 = self
+            .interface
+            .read_register(BME680_CONF_ODR_RUN_GAS_NBC_ADDR)?;
+        let (reg, element_index) = parallel_profile_regs(
+            &self.calib,
+            self.variant,
+            ctrl_gas_1,
+            ambient_temperature,
+            shared_duration,
+            steps,
+        )?;
+        self.bme680_set_regs(&reg[0..element_index])?;
+        self.gas_sett.record_heater_profile(
+            ambient_temperature,
+            steps
+                .iter()
+                .map(|step| (step.temperature, shared_duration * step.multiplier as u32)),
+        );
+        self.gas_sett.record_all_heater_steps(steps.len());
+        self.gas_sett.heatr_dur = Some(shared_duration);
+        Ok(())
+    }
 
-            reg[element_index] = (BME680_CONF_ODR_RUN_GAS_NBC_ADDR, data);
-            element_index += 1;
+    /// Configures the heater profile used in sequential mode, BME688 only
+    ///
+    /// In sequential mode the sensor performs one measurement per step, using the step's
+    /// heater temperature and duration, and writes the results to its three fields in turn.
+    /// Start the measurements by setting [`PowerMode::SequentialMode`] and read them with a
+    /// [`FieldDataReader`].
+    ///
+    /// # Arguments
+    ///
+    /// * `ambient_temperature` - Ambient temperature in degree celsius used to compute the heater resistance
+    pub fn set_sequential_heater_profile(
+        &mut self,
+        delay: &mut D,
+        ambient_temperature: i8,
+        steps: &[HeaterStep],
+    ) -> Result<(), IF::ReadError, IF::WriteError> {
+        if !self.variant.supports(PowerMode::SequentialMode) {
+            return Err(Error::DefinePwrMode);
         }
 
+        self.set_sensor_mode(delay, PowerMode::SleepMode)?;
+
+        let ctrl_gas_1 = self
+            .interface
+            .read_register(BME680_CONF_ODR_RUN_GAS_NBC_ADDR)?;
+        let (reg, element_index) = sequential_profile_regs(
+            &self.calib,
+            self.variant,
+            ctrl_gas_1,
+            ambient_temperature,
+            steps,
+        )?;
         self.bme680_set_regs(&reg[0..element_index])?;
-
-        // Restore previous intended power mode
-        self.power_mode = intended_power_mode;
-        self.tph_sett = tph_sett;
+        self.gas_sett.record_heater_profile(
+            ambient_temperature,
+            steps.iter().map(|step| (step.temperature, step.duration)),
+        );
+        self.gas_sett.record_all_heater_steps(steps.len());
         Ok(())
     }
 
@@ -650,49 +1922,25 @@ where
     pub fn get_sensor_settings(
         &mut self,
         desired_settings: DesiredSensorSettings,
-    ) -> Result<SensorSettings, <I2C as Read>::Error, <I2C as Write>::Error> {
+    ) -> Result<SensorSettings, IF::ReadError, IF::WriteError> {
         let reg_addr: u8 = 0x70u8;
         let mut data_array: [u8; BME680_REG_BUFFER_LENGTH] = [0; BME680_REG_BUFFER_LENGTH];
         let mut sensor_settings: SensorSettings = Default::default();
         sensor_settings.tph_sett.temperature_offset = self.tph_sett.temperature_offset;
+        sensor_settings.tph_sett.temperature_offset_centi = self.tph_sett.temperature_offset_centi;
 
-        I2CUtil::read_bytes(&mut self.i2c, self.dev_id.addr(), reg_addr, &mut data_array)?;
+        self.interface.read_registers(reg_addr, &mut data_array)?;
 
         if desired_settings.contains(DesiredSensorSettings::GAS_MEAS_SEL) {
-            sensor_settings.gas_sett = self.get_gas_config()?;
-        }
-
-        if desired_settings.contains(DesiredSensorSettings::FILTER_SEL) {
-            sensor_settings.tph_sett.filter = Some(IIRFilterSize::from_u8(
-                ((data_array[5usize] as i32 & 0x1ci32) >> 2i32) as u8,
-            ));
-        }
-
-        if desired_settings
-            .contains(DesiredSensorSettings::OST_SEL | DesiredSensorSettings::OSP_SEL)
-        {
-            let os_temp: u8 = ((data_array[4usize] as i32 & 0xe0i32) >> 5i32) as u8;
-            let os_pres: u8 = ((data_array[4usize] as i32 & 0x1ci32) >> 2i32) as u8;
-            sensor_settings.tph_sett.os_temp = Some(OversamplingSetting::from_u8(os_temp));
-            sensor_settings.tph_sett.os_pres = Some(OversamplingSetting::from_u8(os_pres));
-        }
-
-        if desired_settings.contains(DesiredSensorSettings::OSH_SEL) {
-            let os_hum: u8 = (data_array[2usize] as i32 & 0x7i32) as u8;
-            sensor_settings.tph_sett.os_hum = Some(OversamplingSetting::from_u8(os_hum));
+            sensor_settings.gas_sett = self.get_gas_config(data_array[1])?;
         }
 
-        if desired_settings.contains(DesiredSensorSettings::HCNTRL_SEL) {
-            sensor_settings.gas_sett.heatr_ctrl = Some((data_array[0usize] as i32 & 0x8i32) as u8);
-        }
-
-        if desired_settings
-            .contains(DesiredSensorSettings::RUN_GAS_SEL | DesiredSensorSettings::NBCONV_SEL)
-        {
-            sensor_settings.gas_sett.nb_conv = (data_array[1usize] as i32 & 0xfi32) as u8;
-            sensor_settings.gas_sett.run_gas_measurement =
-                ((data_array[1usize] as i32 & 0x10i32) >> 4i32) == 0;
-        }
+        sensor_settings_from_regs(
+            self.variant,
+            desired_settings,
+            &data_array,
+            &mut sensor_settings,
+        );
 
         Ok(sensor_settings)
     }
@@ -706,14 +1954,17 @@ where
         &mut self,
         delay: &mut D,
         target_power_mode: PowerMode,
-    ) -> Result<(), <I2C as Read>::Error, <I2C as Write>::Error> {
+    ) -> Result<(), IF::ReadError, IF::WriteError> {
+        if !self.variant.supports(target_power_mode) {
+            return Err(Error::DefinePwrMode);
+        }
+
         let mut tmp_pow_mode: u8;
         let mut current_power_mode: PowerMode;
 
         // Call repeatedly until in sleep
         loop {
-            tmp_pow_mode =
-                I2CUtil::read_byte(&mut self.i2c, self.dev_id.addr(), BME680_CONF_T_P_MODE_ADDR)?;
+            tmp_pow_mode = self.interface.read_register(BME680_CONF_T_P_MODE_ADDR)?;
 
             // Put to sleep before changing mode
             current_power_mode = PowerMode::from(tmp_pow_mode & BME680_MODE_MSK);
@@ -738,15 +1989,13 @@ where
             debug!("Already in sleep Target power mode: {}", tmp_pow_mode);
             self.bme680_set_regs(&[(BME680_CONF_T_P_MODE_ADDR, tmp_pow_mode)])?;
         }
+        self.power_mode = target_power_mode;
         Ok(())
     }
 
     /// Retrieve current sensor power mode via registers
-    pub fn get_sensor_mode(
-        &mut self,
-    ) -> Result<PowerMode, <I2C as Read>::Error, <I2C as Write>::Error> {
-        let regs =
-            I2CUtil::read_byte(&mut self.i2c, self.dev_id.addr(), BME680_CONF_T_P_MODE_ADDR)?;
+    pub fn get_sensor_mode(&mut self) -> Result<PowerMode, IF::ReadError, IF::WriteError> {
+        let regs = self.interface.read_register(BME680_CONF_T_P_MODE_ADDR)?;
         let mode = regs & BME680_MODE_MSK;
         Ok(PowerMode::from(mode))
     }
@@ -757,7 +2006,7 @@ where
         // TODO replace once https://github.com/rust-lang/rust/pull/50167 has been merged
         const MILLIS_PER_SEC: u64 = 1_000;
         const NANOS_PER_MILLI: u64 = 1_000_000;
-        let millis = (duration.as_secs() as u64 * MILLIS_PER_SEC)
+        let millis = (duration.as_secs() * MILLIS_PER_SEC)
             + (duration.subsec_nanos() as u64 / NANOS_PER_MILLI);
 
         let mut meas_cycles = os_to_meas_cycles
@@ -783,215 +2032,135 @@ where
     pub fn get_profile_dur(
         &self,
         sensor_settings: &SensorSettings,
-    ) -> Result<Duration, <I2C as Read>::Error, <I2C as Write>::Error> {
-        let os_to_meas_cycles: [u8; 6] = [0u8, 1u8, 2u8, 4u8, 8u8, 16u8];
-        // TODO check if the following unwrap_ors do not change behaviour
-        let mut meas_cycles = os_to_meas_cycles[sensor_settings
-            .tph_sett
-            .os_temp
-            .unwrap_or(OversamplingSetting::OSNone)
-            as usize] as u32;
-        meas_cycles = meas_cycles.wrapping_add(
-            os_to_meas_cycles[sensor_settings
-                .tph_sett
-                .os_pres
-                .unwrap_or(OversamplingSetting::OSNone) as usize] as u32,
-        );
-        meas_cycles = meas_cycles.wrapping_add(
-            os_to_meas_cycles[sensor_settings
-                .tph_sett
-                .os_hum
-                .unwrap_or(OversamplingSetting::OSNone) as usize] as u32,
-        );
-        let mut tph_dur = meas_cycles.wrapping_mul(1963u32);
-        tph_dur = tph_dur.wrapping_add(477u32.wrapping_mul(4u32));
-        tph_dur = tph_dur.wrapping_add(477u32.wrapping_mul(5u32));
-        tph_dur = tph_dur.wrapping_add(500u32);
-        tph_dur = tph_dur.wrapping_div(1000u32);
-        tph_dur = tph_dur.wrapping_add(1u32);
-        let mut duration = Duration::from_millis(tph_dur as u64);
-        if sensor_settings.gas_sett.run_gas_measurement {
-            duration += sensor_settings.gas_sett.heatr_dur.expect("Heatrdur");
-        }
-        Ok(duration)
+    ) -> Result<Duration, IF::ReadError, IF::WriteError> {
+        Ok(profile_dur(sensor_settings))
     }
 
-    fn get_calib_data<I2CX>(
-        i2c: &mut I2CX,
-        dev_id: I2CAddress,
-    ) -> Result<CalibData, <I2CX as Read>::Error, <I2CX as Write>::Error>
-    where
-        I2CX: Read + Write,
-    {
-        let mut calib: CalibData = Default::default();
+    /// Reads the calibration data until it is plausible, at most `attempts` times
+    fn read_calib_data(
+        interface: &mut IF,
+        delay: &mut D,
+        attempts: u8,
+    ) -> Result<CalibData, IF::ReadError, IF::WriteError> {
+        let mut attempt = 1;
+        loop {
+            debug!("Reading calib data, attempt {}", attempt);
+            let calib = Bme680::<IF, D>::get_calib_data(interface)?;
+            match validate_calib(calib) {
+                Err(Error::InvalidCalibration(_)) if attempt < attempts => {
+                    delay.delay_ms(BME680_POLL_PERIOD_MS);
+                    attempt += 1;
+                }
+                result => return result,
+            }
+        }
+    }
 
+    fn get_calib_data(interface: &mut IF) -> Result<CalibData, IF::ReadError, IF::WriteError> {
         let mut coeff_array: [u8; BME680_COEFF_ADDR1_LEN + BME680_COEFF_ADDR2_LEN] =
             [0; BME680_COEFF_ADDR1_LEN + BME680_COEFF_ADDR2_LEN];
 
-        I2CUtil::read_bytes::<I2CX>(
-            i2c,
-            dev_id.addr(),
+        interface.read_registers(
             BME680_COEFF_ADDR1,
             &mut coeff_array[0..(BME680_COEFF_ADDR1_LEN - 1)],
         )?;
 
-        I2CUtil::read_bytes::<I2CX>(
-            i2c,
-            dev_id.addr(),
+        interface.read_registers(
             BME680_COEFF_ADDR2,
             &mut coeff_array
                 [BME680_COEFF_ADDR1_LEN..(BME680_COEFF_ADDR1_LEN + BME680_COEFF_ADDR2_LEN - 1)],
         )?;
 
-        calib.par_t1 = ((coeff_array[34usize] as i32) << 8i32 | coeff_array[33usize] as i32) as u16;
-        calib.par_t2 = ((coeff_array[2usize] as i32) << 8i32 | coeff_array[1usize] as i32) as i16;
-        calib.par_t3 = coeff_array[3usize] as i8;
-        calib.par_p1 = ((coeff_array[6usize] as i32) << 8i32 | coeff_array[5usize] as i32) as u16;
-        calib.par_p2 = ((coeff_array[8usize] as i32) << 8i32 | coeff_array[7usize] as i32) as i16;
-        calib.par_p3 = coeff_array[9usize] as i8;
-        calib.par_p4 = ((coeff_array[12usize] as i32) << 8i32 | coeff_array[11usize] as i32) as i16;
-        calib.par_p5 = ((coeff_array[14usize] as i32) << 8i32 | coeff_array[13usize] as i32) as i16;
-        calib.par_p6 = coeff_array[16usize] as i8;
-        calib.par_p7 = coeff_array[15usize] as i8;
-        calib.par_p8 = ((coeff_array[20usize] as i32) << 8i32 | coeff_array[19usize] as i32) as i16;
-        calib.par_p9 = ((coeff_array[22usize] as i32) << 8i32 | coeff_array[21usize] as i32) as i16;
-        calib.par_p10 = coeff_array[23usize];
-        calib.par_h1 =
-            ((coeff_array[27usize] as i32) << 4i32 | coeff_array[26usize] as i32 & 0xfi32) as u16;
-        calib.par_h2 =
-            ((coeff_array[25usize] as i32) << 4i32 | coeff_array[26usize] as i32 >> 4i32) as u16;
-        calib.par_h3 = coeff_array[28usize] as i8;
-        calib.par_h4 = coeff_array[29usize] as i8;
-        calib.par_h5 = coeff_array[30usize] as i8;
-        calib.par_h6 = coeff_array[31usize];
-        calib.par_h7 = coeff_array[32usize] as i8;
-        calib.par_gh1 = coeff_array[37usize] as i8;
-        calib.par_gh2 =
-            ((coeff_array[36usize] as i32) << 8i32 | coeff_array[35usize] as i32) as i16;
-        calib.par_gh3 = coeff_array[38usize] as i8;
-
-        calib.res_heat_range =
-            (I2CUtil::read_byte::<I2CX>(i2c, dev_id.addr(), BME680_ADDR_RES_HEAT_RANGE_ADDR)?
-                & 0x30)
-                / 16;
-
-        calib.res_heat_val =
-            I2CUtil::read_byte::<I2CX>(i2c, dev_id.addr(), BME680_ADDR_RES_HEAT_VAL_ADDR)? as i8;
-
-        calib.range_sw_err =
-            (I2CUtil::read_byte::<I2CX>(i2c, dev_id.addr(), BME680_ADDR_RANGE_SW_ERR_ADDR)?
-                & BME680_RSERROR_MSK)
-                / 16;
-
-        Ok(calib)
-    }
-
-    fn set_gas_config(
-        &mut self,
-        gas_sett: GasSett,
-    ) -> Result<(), <I2C as Read>::Error, <I2C as Write>::Error> {
-        if self.power_mode != PowerMode::ForcedMode {
-            return Err(Error::DefinePwrMode);
-        }
+        let res_heat_range = interface.read_register(BME680_ADDR_RES_HEAT_RANGE_ADDR)?;
+        let res_heat_val = interface.read_register(BME680_ADDR_RES_HEAT_VAL_ADDR)?;
+        let range_sw_err = interface.read_register(BME680_ADDR_RANGE_SW_ERR_ADDR)?;
 
-        // TODO check whether unwrap_or changes behaviour
-        let reg: [(u8, u8); 2] = [
-            (
-                BME680_RES_HEAT0_ADDR,
-                Calc::calc_heater_res(
-                    &self.calib,
-                    gas_sett.ambient_temperature,
-                    gas_sett.heatr_temp.unwrap_or(0),
-                ),
-            ),
-            (
-                BME680_GAS_WAIT0_ADDR,
-                Calc::calc_heater_dur(gas_sett.heatr_dur.unwrap_or_else(|| Duration::from_secs(0))),
-            ),
-        ];
-
-        self.gas_sett.nb_conv = 0;
-        self.bme680_set_regs(&reg)
+        Ok(calib_data_from_regs(
+            &coeff_array,
+            res_heat_range,
+            res_heat_val,
+            range_sw_err,
+        ))
     }
 
-    fn get_gas_config(&mut self) -> Result<GasSett, <I2C as Read>::Error, <I2C as Write>::Error> {
-        let heatr_temp = Some(I2CUtil::read_byte(
-            &mut self.i2c,
-            self.dev_id.addr(),
-            BME680_ADDR_SENS_CONF_START,
-        )? as u16);
-
-        let heatr_dur_ms = I2CUtil::read_byte(
-            &mut self.i2c,
-            self.dev_id.addr(),
-            BME680_ADDR_GAS_CONF_START,
-        )? as u64;
-
-        let gas_sett = GasSett {
-            heatr_temp,
-            heatr_dur: Some(Duration::from_millis(heatr_dur_ms)),
-            ..Default::default()
-        };
+    /// Reads the heater set-points, `ctrl_gas_1` selects the set-point in use
+    ///
+    /// The heater temperatures are decoded using the ambient temperature of the current
+    /// gas settings.
+    fn get_gas_config(&mut self, ctrl_gas_1: u8) -> Result<GasSett, IF::ReadError, IF::WriteError> {
+        let mut res_heat = [0; BME680_HEATER_STEPS];
+        let mut gas_wait = [0; BME680_HEATER_STEPS];
+        self.interface
+            .read_registers(BME680_ADDR_SENS_CONF_START, &mut res_heat)?;
+        self.interface
+            .read_registers(BME680_ADDR_GAS_CONF_START, &mut gas_wait)?;
+
+        Ok(gas_config_from_regs(
+            &self.calib,
+            self.gas_sett.ambient_temperature,
+            ctrl_gas_1,
+            &res_heat,
+            &gas_wait,
+        ))
+    }
 
-        Ok(gas_sett)
+    /// Reads all fields with new data, ordered by measurement index
+    ///
+    /// In parallel mode the BME688 writes its results to three fields in turn. Fields that
+    /// were already read before are still marked as new, see `FieldData::meas_index`.
+    pub fn get_all_field_data(&mut self) -> Result<FieldDataSet, IF::ReadError, IF::WriteError> {
+        let mut buff = [0; BME680_FIELD_LENGTH * BME688_FIELDS];
+        self.interface
+            .read_registers(BME680_FIELD0_ADDR, &mut buff)?;
+        debug!("Field data read {:?}", buff);
+
+        field_data_set_from_regs(
+            &buff,
+            &self.calib,
+            self.variant,
+            self.compensation,
+            &self.tph_sett,
+        )
+        .map_err(Error::Compensation)
     }
 
     /// Retrieve the current sensor informations
     pub fn get_sensor_data(
         &mut self,
         delay: &mut D,
-    ) -> Result<(FieldData, FieldDataCondition), <I2C as Read>::Error, <I2C as Write>::Error> {
-        let mut buff: [u8; BME680_FIELD_LENGTH] = [0; BME680_FIELD_LENGTH];
+    ) -> Result<(FieldData, FieldDataCondition), IF::ReadError, IF::WriteError> {
+        let (raw, condition) = self.get_raw_sensor_data(delay)?;
+        let data = field_data_from_raw(&raw, &self.calib, self.compensation, &self.tph_sett)
+            .map_err(Error::Compensation)?;
+        Ok((data, condition))
+    }
 
-        debug!("Buf {:?}, len: {}", buff, buff.len());
-        let mut data: FieldData = Default::default();
+    /// Retrieve the current sensor values without compensating them
+    ///
+    /// Together with [`calib_data`](Self::calib_data) this allows to store the raw values
+    /// and compensate them later on using [`compensate`].
+    pub fn get_raw_sensor_data(
+        &mut self,
+        delay: &mut D,
+    ) -> Result<(RawFieldData, FieldDataCondition), IF::ReadError, IF::WriteError> {
+        let mut buff: [u8; BME680_FIELD_LENGTH] = [0; BME680_FIELD_LENGTH];
+        let mut raw: RawFieldData = Default::default();
 
         const TRIES: u8 = 10;
         for _ in 0..TRIES {
-            I2CUtil::read_bytes(
-                &mut self.i2c,
-                self.dev_id.addr(),
-                BME680_FIELD0_ADDR,
-                &mut buff,
-            )?;
+            self.interface
+                .read_registers(BME680_FIELD0_ADDR, &mut buff[..self.variant.field_length()])?;
 
             debug!("Field data read {:?}, len: {}", buff, buff.len());
 
-            data.status = buff[0] & BME680_NEW_DATA_MSK;
-            data.gas_index = buff[0] & BME680_GAS_INDEX_MSK;
-            data.meas_index = buff[1];
-
-            let adc_pres = (buff[2] as u32).wrapping_mul(4096)
-                | (buff[3] as u32).wrapping_mul(16)
-                | (buff[4] as u32).wrapping_div(16);
-            let adc_temp = (buff[5] as u32).wrapping_mul(4096)
-                | (buff[6] as u32).wrapping_mul(16)
-                | (buff[7] as u32).wrapping_div(16);
-            let adc_hum = ((buff[8] as u32).wrapping_mul(256) | buff[9] as u32) as u16;
-            let adc_gas_res =
-                ((buff[13] as u32).wrapping_mul(4) | (buff[14] as u32).wrapping_div(64)) as u16;
-            let gas_range = buff[14] & BME680_GAS_RANGE_MSK;
-
-            data.status |= buff[14] & BME680_GASM_VALID_MSK;
-            data.status |= buff[14] & BME680_HEAT_STAB_MSK;
-
-            if data.status & BME680_NEW_DATA_MSK != 0 {
-                let (temp, t_fine) =
-                    Calc::calc_temperature(&self.calib, adc_temp, self.tph_sett.temperature_offset);
-                debug!(
-                    "adc_temp: {} adc_pres: {} adc_hum: {} adc_gas_res: {}, t_fine: {}",
-                    adc_temp, adc_pres, adc_hum, adc_gas_res, t_fine
-                );
-                data.temperature = temp;
-                data.pressure = Calc::calc_pressure(&self.calib, t_fine, adc_pres);
-                data.humidity = Calc::calc_humidity(&self.calib, t_fine, adc_hum);
-                data.gas_resistance =
-                    Calc::calc_gas_resistance(&self.calib, adc_gas_res, gas_range);
-                return Ok((data, FieldDataCondition::NewData));
+            raw = raw_field_data_from_regs(&buff, self.variant);
+
+            if raw.status & BME680_NEW_DATA_MSK != 0 {
+                return Ok((raw, FieldDataCondition::NewData));
             }
 
             delay.delay_ms(BME680_POLL_PERIOD_MS);
         }
-        Ok((data, FieldDataCondition::Unchanged))
+        Ok((raw, FieldDataCondition::Unchanged))
     }
 }
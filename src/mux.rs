@@ -0,0 +1,271 @@
+//! Sensors behind a TCA9548A I²C multiplexer.
+//!
+//! Only two I²C addresses are available for the BME680. More sensors can be connected by
+//! placing them on the channels of a TCA9548A multiplexer. [`MuxInterface`] selects the
+//! channel of a sensor before every bus access, [`MuxedSensors`] manages a sensor on each
+//! channel of a multiplexer.
+//!
+//! ```no_run
+//! use bme680::mux::{MuxedSensors, TCA9548A_ADDR};
+//! use bme680::{I2CAddress, OversamplingSetting, SettingsBuilder};
+//! use core::cell::RefCell;
+//! # use embedded_hal::blocking::{delay, i2c};
+//! # struct Delay;
+//! # impl delay::DelayMs<u8> for Delay {
+//! #     fn delay_ms(&mut self, _ms: u8) {}
+//! # }
+//! # struct I2cdev;
+//! # impl i2c::Write for I2cdev {
+//! #     type Error = ();
+//! #     fn write(&mut self, _addr: u8, _bytes: &[u8]) -> Result<(), ()> { Ok(()) }
+//! # }
+//! # impl i2c::WriteRead for I2cdev {
+//! #     type Error = ();
+//! #     fn write_read(&mut self, _addr: u8, _bytes: &[u8], _buffer: &mut [u8]) -> Result<(), ()> { Ok(()) }
+//! # }
+//!
+//! # fn main() -> Result<(), bme680::Error<(), ()>> {
+//! let bus = RefCell::new(I2cdev);
+//! let mut delayer = Delay;
+//! let mut sensors = MuxedSensors::init(&bus, TCA9548A_ADDR, I2CAddress::Primary, &mut delayer)?;
+//! let settings = SettingsBuilder::new()
+//!     .with_temperature_oversampling(OversamplingSetting::OS8x)
+//!     .with_run_gas(false)
+//!     .build();
+//! sensors.set_sensor_settings(&mut delayer, settings)?;
+//!
+//! for reading in sensors.measure(&mut delayer)?.iter().flatten() {
+//!     println!("Channel {}: {}°C", reading.channel, reading.data.temperature_celsius());
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::hal::blocking::delay::DelayMs;
+use crate::hal::blocking::i2c::{Write, WriteRead};
+use crate::interface::burst;
+use crate::{
+    delay_for, Bme680, Error, FieldData, FieldDataCondition, I2CAddress, Interface, PowerMode,
+    Result, Settings, SharedI2c, BME680_TMP_BUFFER_LENGTH,
+};
+use core::cell::RefCell;
+use core::time::Duration;
+use log::debug;
+
+/// Default I²C address of the TCA9548A, with A0 to A2 connected to GND
+pub const TCA9548A_ADDR: u8 = 0x70;
+
+/// Number of channels of the TCA9548A
+pub const MUX_CHANNELS: usize = 8;
+
+/// I²C interface to a sensor on a channel of a TCA9548A multiplexer
+///
+/// The channel is selected before every register access, so the bus can be shared with
+/// sensors on other channels.
+#[derive(Debug)]
+pub struct MuxInterface<I2C> {
+    i2c: I2C,
+    mux_addr: u8,
+    channel: u8,
+    dev_id: I2CAddress,
+}
+
+impl<I2C> MuxInterface<I2C> {
+    /// Creates the interface to the sensor at `dev_id` on `channel` of the multiplexer at `mux_addr`
+    ///
+    /// # Panics
+    ///
+    /// Panics if `channel` is not a valid multiplexer channel, i.e. not below [`MUX_CHANNELS`].
+    pub fn new(i2c: I2C, mux_addr: u8, channel: u8, dev_id: I2CAddress) -> MuxInterface<I2C> {
+        assert!((channel as usize) < MUX_CHANNELS, "Invalid mux channel");
+        MuxInterface {
+            i2c,
+            mux_addr,
+            channel,
+            dev_id,
+        }
+    }
+
+    /// Multiplexer channel of the sensor
+    pub fn channel(&self) -> u8 {
+        self.channel
+    }
+
+    /// Returns the underlying bus
+    pub fn release(self) -> I2C {
+        self.i2c
+    }
+}
+
+impl<I2C> MuxInterface<I2C>
+where
+    I2C: WriteRead + Write,
+{
+    fn select_channel(&mut self) -> Result<(), <I2C as WriteRead>::Error, <I2C as Write>::Error> {
+        self.i2c
+            .write(self.mux_addr, &[1 << self.channel])
+            .map_err(Error::I2CWrite)
+    }
+
+    /// Checks whether the sensor on the channel acknowledges its address and chip id
+    ///
+    /// Fails only if the channel cannot be selected.
+    fn is_present(&mut self) -> Result<bool, <I2C as WriteRead>::Error, <I2C as Write>::Error> {
+        self.select_channel()?;
+        Ok(crate::is_present(&mut self.i2c, self.dev_id))
+    }
+}
+
+impl<I2C> Interface for MuxInterface<I2C>
+where
+    I2C: WriteRead + Write,
+{
+    type ReadError = <I2C as WriteRead>::Error;
+    type WriteError = <I2C as Write>::Error;
+
+    fn i2c_address(&self) -> Option<I2CAddress> {
+        Some(self.dev_id)
+    }
+
+    fn read_registers(
+        &mut self,
+        reg_addr: u8,
+        buf: &mut [u8],
+    ) -> Result<(), Self::ReadError, Self::WriteError> {
+        self.select_channel()?;
+        self.i2c
+            .write_read(self.dev_id.addr(), &[reg_addr], buf)
+            .map_err(Error::I2CRead)
+    }
+
+    fn write_registers(
+        &mut self,
+        reg: &[(u8, u8)],
+    ) -> Result<(), Self::ReadError, Self::WriteError> {
+        self.select_channel()?;
+        let mut buff = [0u8; BME680_TMP_BUFFER_LENGTH];
+        for chunk in reg.chunks(BME680_TMP_BUFFER_LENGTH / 2) {
+            self.i2c
+                .write(self.dev_id.addr(), burst(chunk, &mut buff))
+                .map_err(Error::I2CWrite)?;
+        }
+        Ok(())
+    }
+}
+
+/// Reading of the sensor on a multiplexer channel
+#[derive(Debug)]
+pub struct ChannelReading {
+    /// Multiplexer channel of the sensor
+    pub channel: u8,
+    pub data: FieldData,
+    pub condition: FieldDataCondition,
+}
+
+/// Sensor on a multiplexer channel
+pub type MuxedSensor<'a, I2C, D> = Bme680<MuxInterface<SharedI2c<'a, I2C>>, D>;
+
+/// Sensors on the channels of a TCA9548A multiplexer
+///
+/// Every sensor is driven by its own [`Bme680`] instance, so each channel keeps its own
+/// calibration data and settings.
+pub struct MuxedSensors<'a, I2C, D> {
+    sensors: [Option<MuxedSensor<'a, I2C, D>>; MUX_CHANNELS],
+    profile_duration: Duration,
+}
+
+impl<'a, I2C, D> MuxedSensors<'a, I2C, D>
+where
+    D: DelayMs<u8>,
+    I2C: WriteRead + Write,
+{
+    /// Initializes the sensors at `dev_id` on every channel of the multiplexer at `mux_addr`
+    ///
+    /// Channels on which no sensor responds are skipped. Errors of responding sensors, e.g.
+    /// failing bus transfers or implausible calibration data, are returned.
+    pub fn init(
+        bus: &'a RefCell<I2C>,
+        mux_addr: u8,
+        dev_id: I2CAddress,
+        delay: &mut D,
+    ) -> Result<Self, <I2C as WriteRead>::Error, <I2C as Write>::Error> {
+        // Deselect all channels, this fails if the multiplexer is missing
+        bus.borrow_mut()
+            .write(mux_addr, &[0])
+            .map_err(Error::I2CWrite)?;
+
+        let mut sensors: [Option<MuxedSensor<'a, I2C, D>>; MUX_CHANNELS] = Default::default();
+        for (channel, sensor) in sensors.iter_mut().enumerate() {
+            let mut interface =
+                MuxInterface::new(SharedI2c::new(bus), mux_addr, channel as u8, dev_id);
+            if interface.is_present()? {
+                *sensor = Some(Bme680::init_with_interface(interface, delay)?);
+            }
+            debug!("Sensor on channel {}: {}", channel, sensor.is_some());
+        }
+
+        Ok(MuxedSensors {
+            sensors,
+            profile_duration: Duration::from_millis(0),
+        })
+    }
+
+    /// Channels with an initialized sensor
+    pub fn channels(&self) -> impl Iterator<Item = u8> + '_ {
+        self.sensors
+            .iter()
+            .enumerate()
+            .filter(|(_, sensor)| sensor.is_some())
+            .map(|(channel, _)| channel as u8)
+    }
+
+    /// Sensor on the given channel
+    pub fn sensor_mut(&mut self, channel: u8) -> Option<&mut MuxedSensor<'a, I2C, D>> {
+        self.sensors.get_mut(channel as usize)?.as_mut()
+    }
+
+    /// Applies the settings to all sensors
+    pub fn set_sensor_settings(
+        &mut self,
+        delay: &mut D,
+        settings: Settings,
+    ) -> Result<(), <I2C as WriteRead>::Error, <I2C as Write>::Error> {
+        for sensor in self.sensors.iter_mut().flatten() {
+            sensor.set_sensor_settings(delay, settings)?;
+            self.profile_duration = sensor.get_profile_dur(&settings.0)?;
+        }
+        Ok(())
+    }
+
+    /// Triggers a measurement on all sensors and reads their data once finished
+    ///
+    /// The returned readings are indexed by channel.
+    pub fn measure(
+        &mut self,
+        delay: &mut D,
+    ) -> Result<
+        [Option<ChannelReading>; MUX_CHANNELS],
+        <I2C as WriteRead>::Error,
+        <I2C as Write>::Error,
+    > {
+        for sensor in self.sensors.iter_mut().flatten() {
+            sensor.set_sensor_mode(delay, PowerMode::ForcedMode)?;
+        }
+
+        // The sensors measure in parallel, so waiting once is sufficient
+        delay_for(delay, self.profile_duration);
+
+        let mut readings: [Option<ChannelReading>; MUX_CHANNELS] = Default::default();
+        for (channel, sensor) in self.sensors.iter_mut().enumerate() {
+            if let Some(sensor) = sensor {
+                let (data, condition) = sensor.get_sensor_data(delay)?;
+                readings[channel] = Some(ChannelReading {
+                    channel: channel as u8,
+                    data,
+                    condition,
+                });
+            }
+        }
+        Ok(readings)
+    }
+}
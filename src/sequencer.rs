@@ -0,0 +1,193 @@
+use crate::hal::blocking::delay::DelayMs;
+use crate::{
+    delay_for, profile_dur, Bme680, DesiredSensorSettings, Error, FieldData, FieldDataCondition,
+    FieldDataSet, HeaterStep, Interface, PowerMode, Result, SensorSettings, Settings,
+    BME680_HEATER_STEPS,
+};
+use log::debug;
+
+/// Readings of a full cycle through the steps of a heater profile
+#[derive(Debug, Default, Clone, Copy)]
+pub struct HeaterScan {
+    readings: [FieldData; BME680_HEATER_STEPS],
+    len: usize,
+}
+
+impl HeaterScan {
+    /// Readings ordered by heater step
+    pub fn readings(&self) -> &[FieldData] {
+        &self.readings[..self.len]
+    }
+}
+
+/// Cycles through the steps of a heater profile, one step per forced measurement
+///
+/// The heater steps are programmed once into the heater set-points of the sensor. Every
+/// [`scan`](Self::scan) then performs a forced measurement for each step, so the gas
+/// resistance is measured at each heater temperature.
+///
+/// # Example
+/// ```no_run
+/// use bme680::{Bme680, HeaterSequencer, HeaterStep, I2CAddress, OversamplingSetting, SettingsBuilder};
+/// use core::time::Duration;
+/// # use embedded_hal::blocking::{delay, i2c};
+/// # struct Delay;
+/// # impl delay::DelayMs<u8> for Delay {
+/// #     fn delay_ms(&mut self, _ms: u8) {}
+/// # }
+/// # struct I2cdev;
+/// # impl i2c::Write for I2cdev {
+/// #     type Error = ();
+/// #     fn write(&mut self, _addr: u8, _bytes: &[u8]) -> Result<(), ()> { Ok(()) }
+/// # }
+/// # impl i2c::WriteRead for I2cdev {
+/// #     type Error = ();
+/// #     fn write_read(&mut self, _addr: u8, _bytes: &[u8], _buffer: &mut [u8]) -> Result<(), ()> { Ok(()) }
+/// # }
+///
+/// # fn main() -> Result<(), bme680::Error<(), ()>> {
+/// let mut delayer = Delay;
+/// let dev = Bme680::init(I2cdev, &mut delayer, I2CAddress::Primary)?;
+/// let settings = SettingsBuilder::new()
+///     .with_temperature_oversampling(OversamplingSetting::OS8x)
+///     .build();
+/// let steps = [200, 250, 300, 350]
+///     .iter()
+///     .map(|temperature| HeaterStep::new(*temperature, Duration::from_millis(150)))
+///     .collect::<Vec<_>>();
+/// let mut sequencer = HeaterSequencer::new(dev, &mut delayer, settings, 25, &steps)?;
+///
+/// let scan = sequencer.scan(&mut delayer)?;
+/// for reading in scan.readings() {
+///     println!("Step {}: {}Ω", reading.gas_index(), reading.gas_resistance_ohm());
+/// }
+/// # Ok(())
+/// # }
+/// ```
+pub struct HeaterSequencer<IF, D> {
+    dev: Bme680<IF, D>,
+    steps: [HeaterStep; BME680_HEATER_STEPS],
+    len: usize,
+    sensor_settings: SensorSettings,
+}
+
+impl<IF, D> HeaterSequencer<IF, D>
+where
+    D: DelayMs<u8>,
+    IF: Interface,
+{
+    /// Applies the settings with gas measurements enabled and programs the heater steps
+    ///
+    /// # Arguments
+    ///
+    /// * `settings` - Temperature, pressure and humidity settings used for every measurement
+    /// * `ambient_temperature` - Ambient temperature in degree celsius used to compute the heater resistance
+    /// * `steps` - Heater steps, at most [`BME680_HEATER_STEPS`]
+    pub fn new(
+        mut dev: Bme680<IF, D>,
+        delay: &mut D,
+        settings: Settings,
+        ambient_temperature: i8,
+        steps: &[HeaterStep],
+    ) -> Result<Self, IF::ReadError, IF::WriteError> {
+        let (mut sensor_settings, mut desired_settings) = settings;
+        sensor_settings.gas_sett.run_gas_measurement = true;
+        sensor_settings.gas_sett.nb_conv = 0;
+        desired_settings |= DesiredSensorSettings::RUN_GAS_SEL | DesiredSensorSettings::NBCONV_SEL;
+        desired_settings.remove(DesiredSensorSettings::GAS_MEAS_SEL);
+
+        dev.set_sensor_settings(delay, (sensor_settings, desired_settings))?;
+        dev.set_heater_profile(delay, ambient_temperature, steps)?;
+
+        let mut sequencer = HeaterSequencer {
+            dev,
+            steps: [HeaterStep::new(0, Default::default()); BME680_HEATER_STEPS],
+            len: steps.len(),
+            sensor_settings,
+        };
+        sequencer.steps[..steps.len()].copy_from_slice(steps);
+        Ok(sequencer)
+    }
+
+    /// Performs a forced measurement for every heater step
+    ///
+    /// Fails with [`Error::NoNewData`] if a measurement did not complete in time.
+    pub fn scan(&mut self, delay: &mut D) -> Result<HeaterScan, IF::ReadError, IF::WriteError> {
+        let mut scan = HeaterScan::default();
+        for (index, step) in self.steps[..self.len].iter().enumerate() {
+            self.dev.select_heater_step(delay, index as u8)?;
+            self.dev.set_sensor_mode(delay, PowerMode::ForcedMode)?;
+
+            let mut step_settings = self.sensor_settings;
+            step_settings.gas_sett.heatr_dur = Some(step.duration);
+            let duration = profile_dur(&step_settings);
+            debug!("Heater step {}, waiting {:?}", index, duration);
+            delay_for(delay, duration);
+
+            let (data, condition) = self.dev.get_sensor_data(delay)?;
+            if condition != FieldDataCondition::NewData {
+                return Err(Error::NoNewData);
+            }
+            scan.readings[index] = data;
+            scan.len += 1;
+        }
+        Ok(scan)
+    }
+
+    /// Heater steps of the profile
+    pub fn steps(&self) -> &[HeaterStep] {
+        &self.steps[..self.len]
+    }
+
+    /// Returns the sensor driver
+    pub fn release(self) -> Bme680<IF, D> {
+        self.dev
+    }
+}
+
+/// Reads the results of parallel or sequential mode, skipping fields already read
+///
+/// The BME688 keeps the new data flag of a field set until it is overwritten, so reading
+/// the fields repeatedly returns the same measurements again. The reader remembers the
+/// measurement index of the newest reading and only returns measurements taken after it.
+#[derive(Debug, Default)]
+pub struct FieldDataReader {
+    last_meas_index: Option<u8>,
+}
+
+impl FieldDataReader {
+    pub fn new() -> FieldDataReader {
+        FieldDataReader::default()
+    }
+
+    /// Reads the fields of the sensor, returning the measurements not read before
+    pub fn read<IF, D>(
+        &mut self,
+        dev: &mut Bme680<IF, D>,
+    ) -> Result<FieldDataSet, IF::ReadError, IF::WriteError>
+    where
+        D: DelayMs<u8>,
+        IF: Interface,
+    {
+        Ok(self.filter_new(dev.get_all_field_data()?))
+    }
+
+    /// Removes the measurements that were already returned from the given fields
+    ///
+    /// Useful with readings obtained otherwise, e.g. from `Bme680Async::get_all_field_data`.
+    pub fn filter_new(&mut self, fields: FieldDataSet) -> FieldDataSet {
+        let mut new = FieldDataSet::default();
+        for data in fields.readings() {
+            let is_new = match self.last_meas_index {
+                Some(last) => data.meas_index.wrapping_sub(last) as i8 > 0,
+                None => true,
+            };
+            if is_new {
+                new.fields[new.len] = *data;
+                new.len += 1;
+                self.last_meas_index = Some(data.meas_index);
+            }
+        }
+        new
+    }
+}
@@ -1,8 +1,10 @@
+use crate::BME680_HEATER_STEPS;
 use bitflags::bitflags;
 use core::time::Duration;
 
 /// Over-sampling settings
 #[derive(Copy, Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[repr(u8)]
 pub enum OversamplingSetting {
     OSNone = 0,
@@ -30,6 +32,7 @@ impl OversamplingSetting {
 
 /// IIR filter settings
 #[derive(Copy, Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[repr(u8)]
 pub enum IIRFilterSize {
     Size0 = 0,
@@ -61,6 +64,7 @@ impl IIRFilterSize {
 
 /// Temperature settings
 #[derive(Debug, Default, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[repr(C)]
 pub struct TphSett {
     /// Humidity oversampling
@@ -73,6 +77,9 @@ pub struct TphSett {
     pub filter: Option<IIRFilterSize>,
     /// If set, the temperature t_fine will be increased by the given value in celsius.
     pub temperature_offset: Option<f32>,
+    /// Temperature offset in 0.01 degree celsius, e.g. 125 for 1.25 °C. Replaces
+    /// `temperature_offset` without floating-point arithmetic for `Compensation::Integer32`.
+    pub temperature_offset_centi: Option<i16>,
 }
 
 impl Clone for TphSett {
@@ -83,8 +90,10 @@ impl Clone for TphSett {
 
 /// Gas measurement settings
 #[derive(Debug, Default, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[repr(C)]
 pub struct GasSett {
+    /// Heater set-point used for gas measurements, 0 to 9
     pub nb_conv: u8,
     /// Heater control
     pub heatr_ctrl: Option<u8>,
@@ -95,6 +104,11 @@ pub struct GasSett {
     /// Profile duration
     pub heatr_dur: Option<Duration>,
     pub ambient_temperature: i8,
+    /// Heater set-points as programmed into the sensor
+    ///
+    /// Decoded from the heater registers by `get_sensor_settings`. The driver keeps the
+    /// set-points it programmed, without errors.
+    pub heater_profile: [HeaterSetPoint; BME680_HEATER_STEPS],
 }
 
 impl Clone for GasSett {
@@ -103,6 +117,123 @@ impl Clone for GasSett {
     }
 }
 
+impl GasSett {
+    /// Keeps the desired settings of `applied` written to the sensor
+    pub(crate) fn record_settings(
+        &mut self,
+        desired_settings: DesiredSensorSettings,
+        applied: &GasSett,
+    ) {
+        if desired_settings.contains(DesiredSensorSettings::GAS_MEAS_SEL) {
+            // The heater settings are written to the set-point of nb_conv
+            if let Some(set_point) = self.heater_profile.get_mut(applied.nb_conv as usize) {
+                *set_point = HeaterSetPoint {
+                    temperature: applied.heatr_temp.unwrap_or(0),
+                    duration: applied.heatr_dur.unwrap_or_default(),
+                    ..Default::default()
+                };
+            }
+            self.heatr_temp = applied.heatr_temp;
+            self.heatr_dur = applied.heatr_dur;
+            self.ambient_temperature = applied.ambient_temperature;
+        }
+        if desired_settings.contains(DesiredSensorSettings::HCNTRL_SEL) {
+            self.heatr_ctrl = applied.heatr_ctrl;
+        }
+        if desired_settings.contains(DesiredSensorSettings::RUN_GAS_SEL) {
+            self.run_gas_measurement = applied.run_gas_measurement;
+        }
+        if desired_settings.contains(DesiredSensorSettings::NBCONV_SEL) {
+            self.nb_conv = applied.nb_conv;
+        }
+    }
+
+    /// Keeps the temperatures and durations of a heater profile starting at set-point 0
+    pub(crate) fn record_heater_profile(
+        &mut self,
+        ambient_temperature: i8,
+        steps: impl Iterator<Item = (u16, Duration)>,
+    ) {
+        for (set_point, (temperature, duration)) in self.heater_profile.iter_mut().zip(steps) {
+            *set_point = HeaterSetPoint {
+                temperature,
+                duration,
+                ..Default::default()
+            };
+        }
+        self.ambient_temperature = ambient_temperature;
+    }
+
+    /// Keeps the set-point selected for the next forced mode gas measurements
+    pub(crate) fn record_heater_step(&mut self, nb_conv: u8) {
+        self.nb_conv = nb_conv;
+        if let Some(set_point) = self.heater_profile.get(nb_conv as usize) {
+            self.heatr_temp = Some(set_point.temperature);
+            self.heatr_dur = Some(set_point.duration);
+        }
+    }
+
+    /// Keeps gas measurements enabled across the first `steps` set-points, as in parallel
+    /// and sequential mode
+    pub(crate) fn record_all_heater_steps(&mut self, steps: usize) {
+        self.nb_conv = steps as u8;
+        self.run_gas_measurement = true;
+    }
+}
+
+/// Heater set-point, one step of a heater profile
+#[derive(Debug, Clone, Copy)]
+pub struct HeaterStep {
+    /// Heater target temperature in degree celsius
+    pub temperature: u16,
+    /// Heating duration
+    pub duration: Duration,
+}
+
+impl HeaterStep {
+    pub fn new(temperature: u16, duration: Duration) -> HeaterStep {
+        HeaterStep {
+            temperature,
+            duration,
+        }
+    }
+}
+
+/// Heater set-point decoded from the heater registers
+///
+/// The heater registers store the target temperature and duration with limited
+/// resolution, the errors give the range of values mapping to the same register value.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct HeaterSetPoint {
+    /// Heater target temperature in degree celsius
+    pub temperature: u16,
+    /// Maximum deviation of the programmed temperature from `temperature`
+    pub temperature_error: u16,
+    /// Heating duration
+    pub duration: Duration,
+    /// Maximum amount the programmed duration may exceed `duration`
+    pub duration_error: Duration,
+}
+
+/// Heater step of a BME688 parallel mode heater profile
+#[derive(Debug, Clone, Copy)]
+pub struct ParallelHeaterStep {
+    /// Heater target temperature in degree celsius
+    pub temperature: u16,
+    /// Heating duration as a multiple of the shared heating duration
+    pub multiplier: u8,
+}
+
+impl ParallelHeaterStep {
+    pub fn new(temperature: u16, multiplier: u8) -> ParallelHeaterStep {
+        ParallelHeaterStep {
+            temperature,
+            multiplier,
+        }
+    }
+}
+
 /// Stores gas and temperature settings
 #[derive(Debug, Default, Copy)]
 pub struct SensorSettings {
@@ -236,6 +367,17 @@ impl SettingsBuilder {
     /// Temperature offset in Celsius, e.g. 4, -8, 1.25
     pub fn with_temperature_offset(mut self, offset: f32) -> SettingsBuilder {
         self.sensor_settings.tph_sett.temperature_offset = Some(offset);
+        self.sensor_settings.tph_sett.temperature_offset_centi = None;
+        self
+    }
+
+    /// Temperature offset in 0.01 degree celsius, e.g. 125 for 1.25 °C
+    ///
+    /// Unlike [`with_temperature_offset`](Self::with_temperature_offset) applied without
+    /// floating-point arithmetic when using `Compensation::Integer32`.
+    pub fn with_temperature_offset_centi(mut self, offset: i16) -> SettingsBuilder {
+        self.sensor_settings.tph_sett.temperature_offset_centi = Some(offset);
+        self.sensor_settings.tph_sett.temperature_offset = None;
         self
     }
 
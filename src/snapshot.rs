@@ -0,0 +1,260 @@
+use crate::settings::{GasSett, HeaterSetPoint, IIRFilterSize, OversamplingSetting, TphSett};
+use crate::{
+    CalibData, ChipVariant, Compensation, I2CAddress, PowerMode, BME680_CALIB_DATA_LEN,
+    BME680_HEATER_STEPS,
+};
+use core::time::Duration;
+
+/// Length of the serialized snapshot, see [`Snapshot::to_bytes`]
+pub const BME680_SNAPSHOT_LEN: usize = 273;
+/// Format version of the serialized snapshot
+const BME680_SNAPSHOT_VERSION: u8 = 1;
+/// Marks an unset oversampling or filter setting in the serialized snapshot
+const NONE: u8 = 0xFF;
+
+/// Driver state of a configured sensor, see `Bme680::snapshot`
+///
+/// The sensor keeps its configuration while powered, so a driver rebuilt from the snapshot
+/// via `Bme680::resume`, e.g. after the MCU woke from deep sleep, can measure right away.
+/// Use [`to_bytes`](Self::to_bytes) or serde to keep it in retained memory.
+#[derive(Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Snapshot {
+    /// I²C address of the sensor, `None` if not connected via I²C
+    pub address: Option<I2CAddress>,
+    pub variant: ChipVariant,
+    pub calib: CalibData,
+    pub compensation: Compensation,
+    pub tph_sett: TphSett,
+    pub gas_sett: GasSett,
+    pub power_mode: PowerMode,
+}
+
+/// Reasons a stored snapshot cannot be restored
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SnapshotError {
+    /// The data is not [`BME680_SNAPSHOT_LEN`] bytes long
+    InvalidLength,
+    /// The data was stored using an unknown format version
+    UnsupportedVersion(u8),
+    /// The named setting holds a value not known to the driver
+    InvalidValue(&'static str),
+    /// The snapshot holds no I²C address, resume the driver via `resume_with_interface`
+    MissingAddress,
+}
+
+/// Reads the serialized snapshot front to back
+struct Reader<'a>(&'a [u8]);
+
+impl<'a> Reader<'a> {
+    fn take<const N: usize>(&mut self) -> [u8; N] {
+        let mut value = [0; N];
+        value.copy_from_slice(&self.0[..N]);
+        self.0 = &self.0[N..];
+        value
+    }
+
+    fn u8(&mut self) -> u8 {
+        self.take::<1>()[0]
+    }
+
+    fn option<const N: usize>(
+        &mut self,
+        name: &'static str,
+    ) -> Result<Option<[u8; N]>, SnapshotError> {
+        let value = self.take::<N>();
+        match self.u8() {
+            0 => Ok(None),
+            1 => Ok(Some(value)),
+            _ => Err(SnapshotError::InvalidValue(name)),
+        }
+    }
+
+    fn duration(&mut self) -> Duration {
+        let secs = u32::from_le_bytes(self.take());
+        let nanos = u32::from_le_bytes(self.take());
+        Duration::new(secs.into(), nanos)
+    }
+
+    fn oversampling(
+        &mut self,
+        name: &'static str,
+    ) -> Result<Option<OversamplingSetting>, SnapshotError> {
+        match self.u8() {
+            NONE => Ok(None),
+            os @ 0..=5 => Ok(Some(OversamplingSetting::from_u8(os))),
+            _ => Err(SnapshotError::InvalidValue(name)),
+        }
+    }
+}
+
+impl Snapshot {
+    /// Serializes the snapshot, e.g. to keep it across deep sleep
+    ///
+    /// The first byte is the format version, followed by the fields in order, little
+    /// endian. Heater durations are limited to `u32::MAX` seconds.
+    pub fn to_bytes(&self) -> [u8; BME680_SNAPSHOT_LEN] {
+        let mut bytes = [0; BME680_SNAPSHOT_LEN];
+        let mut len = 0;
+        let mut put = |value: &[u8]| {
+            bytes[len..len + value.len()].copy_from_slice(value);
+            len += value.len();
+        };
+        // Other options are stored as their value followed by 1 if set, 0 otherwise
+        let duration = |duration: Duration| {
+            let mut bytes = [0; 8];
+            bytes[..4].copy_from_slice(&(duration.as_secs() as u32).to_le_bytes());
+            bytes[4..].copy_from_slice(&duration.subsec_nanos().to_le_bytes());
+            bytes
+        };
+        let oversampling = |os: Option<OversamplingSetting>| os.map_or(NONE, |os| os as u8);
+
+        put(&[BME680_SNAPSHOT_VERSION]);
+        put(&match self.address {
+            None => [0, 0],
+            Some(I2CAddress::Primary) => [1, 0],
+            Some(I2CAddress::Secondary) => [2, 0],
+            Some(I2CAddress::Other(addr)) => [3, addr],
+        });
+        put(&[match self.variant {
+            ChipVariant::Bme680 => 0,
+            ChipVariant::Bme688 => 1,
+        }]);
+        put(&self.calib.to_bytes());
+        put(&[match self.compensation {
+            Compensation::Integer => 0,
+            Compensation::Integer32 => 1,
+            Compensation::Float => 2,
+        }]);
+        put(&[self.power_mode.value()]);
+
+        let tph_sett = &self.tph_sett;
+        put(&[
+            oversampling(tph_sett.os_hum),
+            oversampling(tph_sett.os_temp),
+            oversampling(tph_sett.os_pres),
+            tph_sett.filter.map_or(NONE, |filter| filter as u8),
+        ]);
+        put(&tph_sett.temperature_offset.unwrap_or(0.0).to_le_bytes());
+        put(&[tph_sett.temperature_offset.is_some() as u8]);
+        put(&tph_sett.temperature_offset_centi.unwrap_or(0).to_le_bytes());
+        put(&[tph_sett.temperature_offset_centi.is_some() as u8]);
+
+        let gas_sett = &self.gas_sett;
+        put(&[gas_sett.nb_conv]);
+        put(&[gas_sett.heatr_ctrl.unwrap_or(0)]);
+        put(&[gas_sett.heatr_ctrl.is_some() as u8]);
+        put(&[gas_sett.run_gas_measurement as u8]);
+        put(&gas_sett.heatr_temp.unwrap_or(0).to_le_bytes());
+        put(&[gas_sett.heatr_temp.is_some() as u8]);
+        put(&duration(gas_sett.heatr_dur.unwrap_or_default()));
+        put(&[gas_sett.heatr_dur.is_some() as u8]);
+        put(&gas_sett.ambient_temperature.to_le_bytes());
+        for set_point in &gas_sett.heater_profile {
+            put(&set_point.temperature.to_le_bytes());
+            put(&set_point.temperature_error.to_le_bytes());
+            put(&duration(set_point.duration));
+            put(&duration(set_point.duration_error));
+        }
+        bytes
+    }
+
+    /// Restores a snapshot serialized by [`to_bytes`](Self::to_bytes)
+    pub fn from_bytes(bytes: &[u8]) -> Result<Snapshot, SnapshotError> {
+        if bytes.len() != BME680_SNAPSHOT_LEN {
+            return Err(SnapshotError::InvalidLength);
+        }
+        if bytes[0] != BME680_SNAPSHOT_VERSION {
+            return Err(SnapshotError::UnsupportedVersion(bytes[0]));
+        }
+        let mut reader = Reader(&bytes[1..]);
+
+        let address = match reader.take() {
+            [0, _] => None,
+            [1, _] => Some(I2CAddress::Primary),
+            [2, _] => Some(I2CAddress::Secondary),
+            [3, addr] => Some(I2CAddress::Other(addr)),
+            _ => return Err(SnapshotError::InvalidValue("address")),
+        };
+        let variant = match reader.u8() {
+            0 => ChipVariant::Bme680,
+            1 => ChipVariant::Bme688,
+            _ => return Err(SnapshotError::InvalidValue("variant")),
+        };
+        let calib = CalibData::from_bytes(&reader.take::<BME680_CALIB_DATA_LEN>())
+            .map_err(|_| SnapshotError::InvalidValue("calib"))?;
+        let compensation = match reader.u8() {
+            0 => Compensation::Integer,
+            1 => Compensation::Integer32,
+            2 => Compensation::Float,
+            _ => return Err(SnapshotError::InvalidValue("compensation")),
+        };
+        let power_mode = match reader.u8() {
+            power_mode @ 0..=3 => PowerMode::from(power_mode),
+            _ => return Err(SnapshotError::InvalidValue("power_mode")),
+        };
+
+        let os_hum = reader.oversampling("os_hum")?;
+        let os_temp = reader.oversampling("os_temp")?;
+        let os_pres = reader.oversampling("os_pres")?;
+        let filter = match reader.u8() {
+            NONE => None,
+            filter @ 0..=7 => Some(IIRFilterSize::from_u8(filter)),
+            _ => return Err(SnapshotError::InvalidValue("filter")),
+        };
+        let tph_sett = TphSett {
+            os_hum,
+            os_temp,
+            os_pres,
+            filter,
+            temperature_offset: reader.option("temperature_offset")?.map(f32::from_le_bytes),
+            temperature_offset_centi: reader
+                .option("temperature_offset_centi")?
+                .map(i16::from_le_bytes),
+        };
+
+        let nb_conv = reader.u8();
+        let heatr_ctrl = reader.option("heatr_ctrl")?.map(|[ctrl]| ctrl);
+        let run_gas_measurement = match reader.u8() {
+            0 => false,
+            1 => true,
+            _ => return Err(SnapshotError::InvalidValue("run_gas_measurement")),
+        };
+        let heatr_temp = reader.option("heatr_temp")?.map(u16::from_le_bytes);
+        let heatr_dur = reader.duration();
+        let heatr_dur = match reader.u8() {
+            0 => None,
+            1 => Some(heatr_dur),
+            _ => return Err(SnapshotError::InvalidValue("heatr_dur")),
+        };
+        let ambient_temperature = i8::from_le_bytes(reader.take());
+        let mut heater_profile = [HeaterSetPoint::default(); BME680_HEATER_STEPS];
+        for set_point in heater_profile.iter_mut() {
+            *set_point = HeaterSetPoint {
+                temperature: u16::from_le_bytes(reader.take()),
+                temperature_error: u16::from_le_bytes(reader.take()),
+                duration: reader.duration(),
+                duration_error: reader.duration(),
+            };
+        }
+        let gas_sett = GasSett {
+            nb_conv,
+            heatr_ctrl,
+            run_gas_measurement,
+            heatr_temp,
+            heatr_dur,
+            ambient_temperature,
+            heater_profile,
+        };
+
+        Ok(Snapshot {
+            address,
+            variant,
+            calib,
+            compensation,
+            tph_sett,
+            gas_sett,
+            power_mode,
+        })
+    }
+}
@@ -0,0 +1,68 @@
+//! Register map of a calibrated BME680 and helpers shared by the integration tests.
+
+// Every test uses only some of the helpers
+#![allow(dead_code)]
+
+use embedded_hal::blocking::delay::DelayMs;
+
+pub struct NoDelay;
+
+impl DelayMs<u8> for NoDelay {
+    fn delay_ms(&mut self, _ms: u8) {}
+}
+
+/// Register map of a calibrated BME680 with new data available
+pub fn registers() -> [u8; 256] {
+    let mut registers = [0; 256];
+    // chip id
+    registers[0xd0] = 0x61;
+    calibrate(&mut registers);
+    // new data available in field 0
+    registers[0x1d] = 0x80;
+    // pressure ADC value 380000 and temperature ADC value 500000 in all fields
+    for field in [0x1d, 0x2e, 0x3f].iter() {
+        registers[field + 2] = 0x5c;
+        registers[field + 3] = 0xc6;
+        registers[field + 5] = 0x7a;
+        registers[field + 6] = 0x12;
+    }
+    registers
+}
+
+/// Writes the calibration parameters of a BME680 to the register map
+pub fn calibrate(registers: &mut [u8; 256]) {
+    let mut set_i16 = |addr: usize, value: i16| {
+        registers[addr..addr + 2].copy_from_slice(&value.to_le_bytes());
+    };
+    // par_t1, par_t2
+    set_i16(0xe9, 26180);
+    set_i16(0x8a, 26257);
+    // par_p1, par_p2, par_p4, par_p5, par_p8, par_p9
+    set_i16(0x8e, 36394u16 as i16);
+    set_i16(0x90, -10478);
+    set_i16(0x94, 7120);
+    set_i16(0x96, -140);
+    set_i16(0x9c, -3);
+    set_i16(0x9e, -2950);
+    // par_gh2
+    set_i16(0xeb, -12100);
+    // par_t3, par_p3, par_p7, par_p6, par_p10
+    registers[0x8c] = 3;
+    registers[0x92] = 88;
+    registers[0x98] = 48;
+    registers[0x99] = 30;
+    registers[0xa0] = 30;
+    // par_h1 = 794 and par_h2 = 1012 share register 0xe2
+    registers[0xe1] = 0x3f;
+    registers[0xe2] = 0x4a;
+    registers[0xe3] = 0x31;
+    // par_h3 to par_h7
+    registers[0xe4..0xe9].copy_from_slice(&[0, 45, 20, 120, (-100i8) as u8]);
+    // par_gh1, par_gh3
+    registers[0xed] = (-30i8) as u8;
+    registers[0xee] = 18;
+    // res_heat_val, res_heat_range, range_sw_err
+    registers[0x00] = 40;
+    registers[0x02] = 0x10;
+    registers[0x04] = 0x10;
+}
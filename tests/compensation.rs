@@ -0,0 +1,620 @@
+//! Golden vectors of the compensation formulas.
+//!
+//! The expected values were computed with the integer formulas of the Bosch reference
+//! driver (BME680 and BME68x). Raw values that overflow the reference arithmetic, such as
+//! the minimum pressure ADC value, or result in a negative pressure have no defined result
+//! there and are rejected with a `CompensationError`. The exception is the cubic pressure
+//! term, which overflows the reference from about 1065 hPa, the expected pressures up to
+//! 1100 hPa were computed without overflow.
+
+use bme680::calc::{Calc, CompensationError};
+use bme680::CalibData;
+use core::time::Duration;
+
+/// Calibration data of two sensors
+const CALIB: [CalibData; 2] = [
+    CalibData {
+        par_h1: 794,
+        par_h2: 1012,
+        par_h3: 0,
+        par_h4: 45,
+        par_h5: 20,
+        par_h6: 120,
+        par_h7: -100,
+        par_gh1: -30,
+        par_gh2: -12100,
+        par_gh3: 18,
+        par_t1: 26180,
+        par_t2: 26257,
+        par_t3: 3,
+        par_p1: 36394,
+        par_p2: -10478,
+        par_p3: 88,
+        par_p4: 7120,
+        par_p5: -140,
+        par_p6: 30,
+        par_p7: 48,
+        par_p8: -3,
+        par_p9: -2950,
+        par_p10: 30,
+        res_heat_range: 1,
+        res_heat_val: 40,
+        range_sw_err: 1,
+    },
+    CalibData {
+        par_h1: 763,
+        par_h2: 1036,
+        par_h3: 0,
+        par_h4: 45,
+        par_h5: 20,
+        par_h6: 120,
+        par_h7: -100,
+        par_gh1: -46,
+        par_gh2: -13855,
+        par_gh3: 18,
+        par_t1: 25944,
+        par_t2: 26703,
+        par_t3: 3,
+        par_p1: 36850,
+        par_p2: -10561,
+        par_p3: 88,
+        par_p4: 6680,
+        par_p5: -166,
+        par_p6: 30,
+        par_p7: 53,
+        par_p8: -231,
+        par_p9: -2650,
+        par_p10: 30,
+        res_heat_range: 2,
+        res_heat_val: -10,
+        range_sw_err: 5,
+    },
+];
+
+/// Calibration set, temperature ADC value, temperature offset, temperature in 0.01 °C, t_fine
+const TEMPERATURE: [(usize, u32, Option<f32>, i16, i32); 22] = [
+    (0, 0, None, -13102, -670808),
+    (0, 1, None, -13102, -670808),
+    (0, 250000, None, -5285, -270569),
+    (0, 400000, None, -591, -30258),
+    (0, 500000, None, 2539, 130020),
+    (0, 500000, Some(1.25), 2664, 136394),
+    (0, 500000, Some(-4.5), 2090, 107006),
+    (0, 500000, Some(0.0), 2539, 130020),
+    (0, 550000, None, 4105, 210180),
+    (0, 750000, None, 10370, 530958),
+    (0, 1048575, None, 19731, 1010245),
+    (1, 0, None, -13204, -676065),
+    (1, 1, None, -13204, -676065),
+    (1, 250000, None, -5254, -269015),
+    (1, 400000, None, -481, -24617),
+    (1, 500000, None, 2703, 138385),
+    (1, 500000, Some(1.25), 2827, 144759),
+    (1, 500000, Some(-4.5), 2253, 115371),
+    (1, 500000, Some(0.0), 2703, 138385),
+    (1, 550000, None, 4295, 219906),
+    (1, 750000, None, 10667, 546133),
+    (1, 1048575, None, 20187, 1033554),
+];
+
+/// Calibration set, t_fine, pressure ADC value, pressure in Pa
+const PRESSURE: [(usize, i32, u32, u32); 37] = [
+    (0, -30258, 300000, 103950),
+    (0, -30258, 360000, 94062),
+    (0, -30258, 500000, 71093),
+    (0, -30258, 650000, 46594),
+    (0, -30258, 800000, 22134),
+    (0, -30258, 900000, 5812),
+    (0, 130020, 360000, 99116),
+    (0, 130020, 500000, 74953),
+    (0, 130020, 650000, 49196),
+    (0, 130020, 800000, 23491),
+    (0, 130020, 900000, 6339),
+    (0, 210180, 360000, 101672),
+    (0, 210180, 500000, 76901),
+    (0, 210180, 650000, 50507),
+    (0, 210180, 800000, 24171),
+    (0, 210180, 900000, 6600),
+    (1, -24617, 300000, 104015),
+    (1, -24617, 360000, 94221),
+    (1, -24617, 500000, 71481),
+    (1, -24617, 650000, 47234),
+    (1, -24617, 800000, 23037),
+    (1, -24617, 900000, 6898),
+    (1, 138385, 360000, 99443),
+    (1, 138385, 500000, 75488),
+    (1, 138385, 650000, 49965),
+    (1, 138385, 800000, 24508),
+    (1, 138385, 900000, 7529),
+    (1, 219906, 360000, 102089),
+    (1, 219906, 500000, 77520),
+    (1, 219906, 650000, 51349),
+    (1, 219906, 800000, 25250),
+    (1, 219906, 900000, 7846),
+    // The cubic term exceeds 32 bits up to the maximum of 1100 hPa
+    (0, -30258, 280000, 107255),
+    (0, 130020, 297263, 110001),
+    (0, 210180, 313153, 110001),
+    (1, 138385, 298639, 110001),
+    (1, 219906, 315177, 110001),
+];
+
+/// Calibration set, t_fine, humidity ADC value, humidity in 0.001 %
+const HUMIDITY: [(usize, i32, u16, u32); 30] = [
+    (0, -30258, 0, 0),
+    (0, -30258, 1, 0),
+    (0, -30258, 20000, 33565),
+    (0, -30258, 27000, 76751),
+    (0, -30258, 40000, 100000),
+    (0, 130020, 0, 0),
+    (0, 130020, 1, 0),
+    (0, 130020, 20000, 36148),
+    (0, 130020, 27000, 81500),
+    (0, 130020, 40000, 100000),
+    (0, 210180, 0, 0),
+    (0, 210180, 1, 0),
+    (0, 210180, 20000, 37820),
+    (0, 210180, 27000, 84591),
+    (0, 210180, 40000, 100000),
+    (1, -24617, 0, 0),
+    (1, -24617, 1, 0),
+    (1, -24617, 20000, 37379),
+    (1, -24617, 27000, 82863),
+    (1, -24617, 40000, 100000),
+    (1, 138385, 0, 0),
+    (1, 138385, 1, 0),
+    (1, 138385, 20000, 40280),
+    (1, 138385, 27000, 88021),
+    (1, 138385, 40000, 100000),
+    (1, 219906, 0, 0),
+    (1, 219906, 1, 0),
+    (1, 219906, 20000, 42152),
+    (1, 219906, 27000, 91356),
+    (1, 219906, 40000, 100000),
+];
+
+/// Calibration set, gas ADC value, gas range, BME680 gas resistance in Ω
+const GAS_RESISTANCE: [(usize, u16, u8, u32); 96] = [
+    (0, 0, 0, 12917167),
+    (0, 512, 0, 8000000),
+    (0, 1023, 0, 5797414),
+    (0, 0, 1, 6458584),
+    (0, 512, 1, 4000000),
+    (0, 1023, 1, 2898707),
+    (0, 0, 2, 3229292),
+    (0, 512, 2, 2000000),
+    (0, 1023, 2, 1449353),
+    (0, 0, 3, 1614646),
+    (0, 512, 3, 1000000),
+    (0, 1023, 3, 724677),
+    (0, 0, 4, 806516),
+    (0, 512, 4, 499500),
+    (0, 1023, 4, 361976),
+    (0, 0, 5, 403360),
+    (0, 512, 5, 248262),
+    (0, 1023, 5, 179411),
+    (0, 0, 6, 201831),
+    (0, 512, 6, 125000),
+    (0, 1023, 6, 90585),
+    (0, 0, 7, 102236),
+    (0, 512, 7, 63004),
+    (0, 1023, 7, 45556),
+    (0, 0, 8, 50508),
+    (0, 512, 8, 31281),
+    (0, 1023, 8, 22669),
+    (0, 0, 9, 25229),
+    (0, 512, 9, 15625),
+    (0, 1023, 9, 11323),
+    (0, 0, 10, 12630),
+    (0, 512, 10, 7813),
+    (0, 1023, 10, 5658),
+    (0, 0, 11, 6327),
+    (0, 512, 11, 3906),
+    (0, 1023, 11, 2827),
+    (0, 0, 12, 3154),
+    (0, 512, 12, 1953),
+    (0, 1023, 12, 1415),
+    (0, 0, 13, 1587),
+    (0, 512, 13, 977),
+    (0, 1023, 13, 706),
+    (0, 0, 14, 788),
+    (0, 512, 14, 488),
+    (0, 1023, 14, 354),
+    (0, 0, 15, 394),
+    (0, 512, 15, 244),
+    (0, 1023, 15, 177),
+    (1, 0, 0, 12801876),
+    (1, 512, 0, 8000000),
+    (1, 1023, 0, 5820895),
+    (1, 0, 1, 6400938),
+    (1, 512, 1, 4000000),
+    (1, 1023, 1, 2910448),
+    (1, 0, 2, 3200469),
+    (1, 512, 2, 2000000),
+    (1, 1023, 2, 1455224),
+    (1, 0, 3, 1600234),
+    (1, 512, 3, 1000000),
+    (1, 1023, 3, 727612),
+    (1, 0, 4, 799318),
+    (1, 512, 4, 499500),
+    (1, 1023, 4, 363443),
+    (1, 0, 5, 399701),
+    (1, 512, 5, 248262),
+    (1, 1023, 5, 180143),
+    (1, 0, 6, 200029),
+    (1, 512, 6, 125000),
+    (1, 1023, 6, 90951),
+    (1, 0, 7, 101312),
+    (1, 512, 7, 63004),
+    (1, 1023, 7, 45742),
+    (1, 0, 8, 50057),
+    (1, 512, 8, 31281),
+    (1, 1023, 8, 22761),
+    (1, 0, 9, 25004),
+    (1, 512, 9, 15625),
+    (1, 1023, 9, 11369),
+    (1, 0, 10, 12517),
+    (1, 512, 10, 7812),
+    (1, 1023, 10, 5681),
+    (1, 0, 11, 6270),
+    (1, 512, 11, 3906),
+    (1, 1023, 11, 2838),
+    (1, 0, 12, 3125),
+    (1, 512, 12, 1953),
+    (1, 1023, 12, 1421),
+    (1, 0, 13, 1572),
+    (1, 512, 13, 977),
+    (1, 1023, 13, 709),
+    (1, 0, 14, 781),
+    (1, 512, 14, 488),
+    (1, 1023, 14, 355),
+    (1, 0, 15, 391),
+    (1, 512, 15, 244),
+    (1, 1023, 15, 178),
+];
+
+/// Gas ADC value, gas range, BME688 gas resistance in Ω
+const GAS_RESISTANCE_HIGH: [(u16, u8, u32); 48] = [
+    (0, 0, 102400000),
+    (512, 0, 64000000),
+    (1023, 0, 46570200),
+    (0, 1, 51200000),
+    (512, 1, 32000000),
+    (1023, 1, 23285100),
+    (0, 2, 25600000),
+    (512, 2, 16000000),
+    (1023, 2, 11642500),
+    (0, 3, 12800000),
+    (512, 3, 8000000),
+    (1023, 3, 5821200),
+    (0, 4, 6400000),
+    (512, 4, 4000000),
+    (1023, 4, 2910600),
+    (0, 5, 3200000),
+    (512, 5, 2000000),
+    (1023, 5, 1455300),
+    (0, 6, 1600000),
+    (512, 6, 1000000),
+    (1023, 6, 727600),
+    (0, 7, 800000),
+    (512, 7, 500000),
+    (1023, 7, 363800),
+    (0, 8, 400000),
+    (512, 8, 250000),
+    (1023, 8, 181900),
+    (0, 9, 200000),
+    (512, 9, 125000),
+    (1023, 9, 90900),
+    (0, 10, 100000),
+    (512, 10, 62500),
+    (1023, 10, 45400),
+    (0, 11, 50000),
+    (512, 11, 31200),
+    (1023, 11, 22700),
+    (0, 12, 25000),
+    (512, 12, 15600),
+    (1023, 12, 11300),
+    (0, 13, 12500),
+    (512, 13, 7800),
+    (1023, 13, 5600),
+    (0, 14, 6200),
+    (512, 14, 3900),
+    (1023, 14, 2800),
+    (0, 15, 3100),
+    (512, 15, 1900),
+    (1023, 15, 1400),
+];
+
+/// Calibration set, ambient temperature, heater temperature, `res_heat_x` register value
+const HEATER_RES: [(usize, i8, u16, u8); 30] = [
+    (0, -10, 0, 34),
+    (0, -10, 200, 85),
+    (0, -10, 320, 116),
+    (0, -10, 400, 136),
+    (0, -10, 450, 136),
+    (0, 25, 0, 34),
+    (0, 25, 200, 85),
+    (0, 25, 320, 116),
+    (0, 25, 400, 136),
+    (0, 25, 450, 136),
+    (0, 40, 0, 34),
+    (0, 40, 200, 85),
+    (0, 40, 320, 116),
+    (0, 40, 400, 136),
+    (0, 40, 450, 136),
+    (1, -10, 0, 21),
+    (1, -10, 200, 67),
+    (1, -10, 320, 95),
+    (1, -10, 400, 113),
+    (1, -10, 450, 113),
+    (1, 25, 0, 21),
+    (1, 25, 200, 67),
+    (1, 25, 320, 95),
+    (1, 25, 400, 113),
+    (1, 25, 450, 113),
+    (1, 40, 0, 21),
+    (1, 40, 200, 67),
+    (1, 40, 320, 95),
+    (1, 40, 400, 113),
+    (1, 40, 450, 113),
+];
+
+/// Heating duration in ms, `gas_wait_x` register value
+const HEATER_DUR: [(u64, u8); 10] = [
+    (0, 0),
+    (1, 1),
+    (63, 63),
+    (64, 80),
+    (100, 89),
+    (255, 127),
+    (1500, 215),
+    (4031, 254),
+    (4032, 255),
+    (5000, 255),
+];
+
+#[test]
+fn temperature_matches_reference() {
+    for &(calib, temp_adc, offset, temperature, t_fine) in TEMPERATURE.iter() {
+        assert_eq!(
+            Calc::calc_temperature(&CALIB[calib], temp_adc, offset),
+            Ok((temperature, t_fine)),
+            "calibration set {}, ADC value {}, offset {:?}",
+            calib,
+            temp_adc,
+            offset
+        );
+    }
+}
+
+#[test]
+fn pressure_matches_reference() {
+    for &(calib, t_fine, pres_adc, pressure) in PRESSURE.iter() {
+        assert_eq!(
+            Calc::calc_pressure(&CALIB[calib], t_fine, pres_adc),
+            Ok(pressure),
+            "calibration set {}, t_fine {}, ADC value {}",
+            calib,
+            t_fine,
+            pres_adc
+        );
+    }
+}
+
+#[test]
+fn humidity_matches_reference() {
+    for &(calib, t_fine, hum_adc, humidity) in HUMIDITY.iter() {
+        assert_eq!(
+            Calc::calc_humidity(&CALIB[calib], t_fine, hum_adc),
+            Ok(humidity),
+            "calibration set {}, t_fine {}, ADC value {}",
+            calib,
+            t_fine,
+            hum_adc
+        );
+    }
+}
+
+#[test]
+fn gas_resistance_matches_reference() {
+    for &(calib, gas_res_adc, gas_range, gas_resistance) in GAS_RESISTANCE.iter() {
+        assert_eq!(
+            Calc::calc_gas_resistance(&CALIB[calib], gas_res_adc, gas_range),
+            Ok(gas_resistance),
+            "calibration set {}, ADC value {}, gas range {}",
+            calib,
+            gas_res_adc,
+            gas_range
+        );
+    }
+    for &(gas_res_adc, gas_range, gas_resistance) in GAS_RESISTANCE_HIGH.iter() {
+        assert_eq!(
+            Calc::calc_gas_resistance_high(gas_res_adc, gas_range),
+            Ok(gas_resistance),
+            "ADC value {}, gas range {}",
+            gas_res_adc,
+            gas_range
+        );
+    }
+}
+
+#[test]
+fn heater_settings_match_reference() {
+    for &(calib, amb_temp, temp, res_heat) in HEATER_RES.iter() {
+        assert_eq!(
+            Calc::calc_heater_res(&CALIB[calib], amb_temp, temp),
+            res_heat,
+            "calibration set {}, ambient temperature {}, temperature {}",
+            calib,
+            amb_temp,
+            temp
+        );
+    }
+    for &(ms, gas_wait) in HEATER_DUR.iter() {
+        assert_eq!(
+            Calc::calc_heater_dur(Duration::from_millis(ms)),
+            gas_wait,
+            "duration {} ms",
+            ms
+        );
+    }
+}
+
+#[test]
+fn raw_values_out_of_adc_range_are_rejected() {
+    use CompensationError::OutOfRange;
+
+    let calib = &CALIB[0];
+    let temperature = Err(OutOfRange("temperature"));
+    assert_eq!(Calc::calc_temperature(calib, 1 << 20, None), temperature);
+    assert_eq!(Calc::calc_temperature_i32(calib, u32::MAX, 0), temperature);
+    assert_eq!(
+        Calc::calc_temperature_float(calib, 1 << 20, None),
+        Err(OutOfRange("temperature"))
+    );
+    assert_eq!(
+        Calc::calc_pressure(calib, 128000, 1 << 20),
+        Err(OutOfRange("pressure"))
+    );
+    assert_eq!(
+        Calc::calc_pressure_float(calib, 128000.0, 1 << 20),
+        Err(OutOfRange("pressure"))
+    );
+
+    for &(gas_res_adc, gas_range, quantity) in [
+        (1 << 10, 0, "gas resistance"),
+        (u16::MAX, 0, "gas resistance"),
+        (512, 16, "gas range"),
+        (512, u8::MAX, "gas range"),
+    ]
+    .iter()
+    {
+        let error = Err(OutOfRange(quantity));
+        assert_eq!(
+            Calc::calc_gas_resistance(calib, gas_res_adc, gas_range),
+            error
+        );
+        assert_eq!(
+            Calc::calc_gas_resistance_i32(calib, gas_res_adc, gas_range),
+            error
+        );
+        assert_eq!(
+            Calc::calc_gas_resistance_high(gas_res_adc, gas_range),
+            error
+        );
+        assert_eq!(
+            Calc::calc_gas_resistance_float(calib, gas_res_adc, gas_range),
+            Err(OutOfRange(quantity))
+        );
+        assert_eq!(
+            Calc::calc_gas_resistance_high_float(gas_res_adc, gas_range),
+            Err(OutOfRange(quantity))
+        );
+    }
+}
+
+#[test]
+fn overflow_and_division_by_zero_are_detected() {
+    use CompensationError::{DivisionByZero, Overflow};
+
+    let calib = &CALIB[0];
+    // The extreme ADC values overflow the reference arithmetic or give a negative pressure
+    for &pres_adc in [0, 100000, 0xfffff].iter() {
+        assert_eq!(
+            Calc::calc_pressure(calib, 128000, pres_adc),
+            Err(Overflow("pressure")),
+            "ADC value {}",
+            pres_adc
+        );
+    }
+    assert_eq!(
+        Calc::calc_pressure(calib, i32::MAX, 400000),
+        Err(Overflow("pressure"))
+    );
+    assert_eq!(
+        Calc::calc_humidity(calib, 128000, u16::MAX),
+        Err(Overflow("humidity"))
+    );
+    assert_eq!(
+        Calc::calc_humidity(calib, i32::MIN, 20000),
+        Err(Overflow("humidity"))
+    );
+    assert_eq!(
+        Calc::calc_temperature(calib, 500000, Some(f32::MAX)),
+        Err(Overflow("temperature"))
+    );
+    assert_eq!(
+        Calc::calc_temperature_float(calib, 500000, Some(f32::INFINITY)),
+        Err(Overflow("temperature"))
+    );
+
+    let calib = CalibData {
+        par_p1: 0,
+        ..CALIB[0]
+    };
+    assert_eq!(
+        Calc::calc_pressure(&calib, 128000, 400000),
+        Err(DivisionByZero("pressure"))
+    );
+    assert_eq!(
+        Calc::calc_pressure_float(&calib, 128000.0, 400000),
+        Err(DivisionByZero("pressure"))
+    );
+}
+
+/// Calibration data with the extreme values of the temperature parameters
+fn extreme_temperature_calib() -> impl Iterator<Item = CalibData> {
+    [0, u16::MAX].iter().flat_map(|&par_t1| {
+        [i16::MIN, i16::MAX].iter().flat_map(move |&par_t2| {
+            [i8::MIN, i8::MAX].iter().map(move |&par_t3| CalibData {
+                par_t1,
+                par_t2,
+                par_t3,
+                ..Default::default()
+            })
+        })
+    })
+}
+
+#[test]
+fn temperature_i32_is_identical_over_full_adc_range() {
+    let calib_offsets = CALIB
+        .iter()
+        .flat_map(|calib| [0, 125, -450].iter().map(move |offset| (*calib, *offset)))
+        .chain(extreme_temperature_calib().map(|calib| (calib, 0)));
+    for (calib, offset) in calib_offsets {
+        let float_offset = Some(offset as f32 / 100.0);
+        for temp_adc in 0..1 << 20 {
+            assert_eq!(
+                Calc::calc_temperature_i32(&calib, temp_adc, offset),
+                Calc::calc_temperature(&calib, temp_adc, float_offset),
+                "{:?}, ADC value {}, offset {}",
+                calib,
+                temp_adc,
+                offset
+            );
+        }
+    }
+}
+
+#[test]
+fn gas_resistance_i32_is_identical_over_full_adc_range() {
+    for range_sw_err in 0..16 {
+        let calib = CalibData {
+            range_sw_err,
+            ..CALIB[0]
+        };
+        for gas_range in 0..16 {
+            for gas_res_adc in 0..1 << 10 {
+                assert_eq!(
+                    Calc::calc_gas_resistance_i32(&calib, gas_res_adc, gas_range),
+                    Calc::calc_gas_resistance(&calib, gas_res_adc, gas_range),
+                    "range_sw_err {}, ADC value {}, gas range {}",
+                    range_sw_err,
+                    gas_res_adc,
+                    gas_range
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,132 @@
+//! Checks the register accesses the driver issues through a user-implemented `Interface`.
+
+use bme680::{Bme680, Error, Interface, OversamplingSetting, PowerMode, SettingsBuilder};
+
+mod common;
+
+use common::NoDelay;
+
+#[derive(Debug, Clone, PartialEq)]
+enum Access {
+    /// Start address and number of registers read
+    Read(u8, usize),
+    Write(Vec<(u8, u8)>),
+}
+
+/// Transport of a custom bus, recording each register access
+struct Registers {
+    registers: [u8; 256],
+    log: Vec<Access>,
+    /// Fails reading the register at this address
+    failing_reg: Option<u8>,
+}
+
+impl Registers {
+    fn new() -> Registers {
+        Registers {
+            registers: common::registers(),
+            log: Vec::new(),
+            failing_reg: None,
+        }
+    }
+}
+
+impl Interface for Registers {
+    type ReadError = &'static str;
+    type WriteError = ();
+
+    fn read_registers(
+        &mut self,
+        reg_addr: u8,
+        buf: &mut [u8],
+    ) -> bme680::Result<(), &'static str, ()> {
+        self.log.push(Access::Read(reg_addr, buf.len()));
+        if self.failing_reg == Some(reg_addr) {
+            return Err(Error::I2CRead("bus fault"));
+        }
+        let start = reg_addr as usize;
+        buf.copy_from_slice(&self.registers[start..start + buf.len()]);
+        Ok(())
+    }
+
+    fn write_registers(&mut self, reg: &[(u8, u8)]) -> bme680::Result<(), &'static str, ()> {
+        self.log.push(Access::Write(reg.to_vec()));
+        for &(reg_addr, value) in reg {
+            self.registers[reg_addr as usize] = value;
+        }
+        // The forced measurement completes immediately
+        self.registers[0x74] &= !0x03;
+        Ok(())
+    }
+}
+
+#[test]
+fn init_with_interface_reads_chip_id_and_calibration() {
+    let dev = Bme680::init_with_interface(Registers::new(), &mut NoDelay).unwrap();
+    assert_eq!(dev.calib_data().par_t1, 26180);
+    assert_eq!(dev.calib_data().par_gh2, -12100);
+
+    let interface = dev.release_interface(&mut NoDelay).unwrap();
+    assert_eq!(
+        interface.log[..8],
+        [
+            // Soft reset
+            Access::Write(vec![(0xe0, 0xb6)]),
+            // Chip id and variant id
+            Access::Read(0xd0, 1),
+            Access::Read(0xf0, 1),
+            // Calibration coefficients
+            Access::Read(0x89, 24),
+            Access::Read(0xe1, 15),
+            // res_heat_range, res_heat_val and range_sw_err
+            Access::Read(0x02, 1),
+            Access::Read(0x00, 1),
+            Access::Read(0x04, 1),
+        ]
+    );
+    // Releasing the interface only checks the mode, the sensor is asleep after the reset
+    assert_eq!(interface.log[8..], [Access::Read(0x74, 1)]);
+}
+
+#[test]
+fn forced_measurement_through_custom_interface() {
+    let mut dev = Bme680::init_with_interface(Registers::new(), &mut NoDelay).unwrap();
+    let settings = SettingsBuilder::new()
+        .with_temperature_oversampling(OversamplingSetting::OS2x)
+        .with_pressure_oversampling(OversamplingSetting::OS2x)
+        .build();
+    dev.set_sensor_settings(&mut NoDelay, settings).unwrap();
+    dev.set_sensor_mode(&mut NoDelay, PowerMode::ForcedMode)
+        .unwrap();
+    let (data, _) = dev.get_sensor_data(&mut NoDelay).unwrap();
+    assert!(data.temperature_celsius() > 0.0);
+
+    let interface = dev.release_interface(&mut NoDelay).unwrap();
+    assert_eq!(
+        interface.log[8..],
+        [
+            // set_sensor_settings reads the mode and the configuration, then writes ctrl_meas
+            Access::Read(0x74, 1),
+            Access::Read(0x70, 6),
+            Access::Write(vec![(0x74, 0x48)]),
+            // Forced mode and the field data
+            Access::Read(0x74, 1),
+            Access::Write(vec![(0x74, 0x49)]),
+            Access::Read(0x1d, 15),
+            // The forced measurement returned the sensor to sleep before the release
+            Access::Read(0x74, 1),
+        ]
+    );
+}
+
+#[test]
+fn errors_of_custom_interface_are_returned() {
+    let mut registers = Registers::new();
+    registers.failing_reg = Some(0x89);
+    let result = Bme680::init_with_interface(registers, &mut NoDelay);
+    assert!(
+        matches!(result, Err(Error::I2CRead("bus fault"))),
+        "{:?}",
+        result.err()
+    );
+}
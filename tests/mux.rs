@@ -0,0 +1,139 @@
+//! Sensors on the channels of a TCA9548A multiplexer.
+
+use bme680::mux::{MuxedSensors, TCA9548A_ADDR};
+use bme680::{I2CAddress, OversamplingSetting, SettingsBuilder};
+use core::cell::RefCell;
+use embedded_hal::blocking::i2c::{Write, WriteRead};
+
+mod common;
+
+use common::NoDelay;
+
+/// Multiplexer with optional sensors at 0x76 on each channel
+struct MuxBus {
+    selected: u8,
+    channels: [Option<[u8; 256]>; 8],
+}
+
+impl MuxBus {
+    fn new(populated: &[usize]) -> MuxBus {
+        let registers = common::registers();
+        let mut channels = [None; 8];
+        for channel in populated {
+            channels[*channel] = Some(registers);
+        }
+        MuxBus {
+            selected: 0,
+            channels,
+        }
+    }
+
+    /// Registers of the sensor at `addr` on the selected channel
+    fn registers(&mut self, addr: u8) -> Result<&mut [u8; 256], ()> {
+        if addr != 0x76 || self.selected.count_ones() != 1 {
+            return Err(());
+        }
+        self.channels[self.selected.trailing_zeros() as usize]
+            .as_mut()
+            .ok_or(())
+    }
+}
+
+impl Write for MuxBus {
+    type Error = ();
+
+    fn write(&mut self, addr: u8, bytes: &[u8]) -> Result<(), Self::Error> {
+        if addr == TCA9548A_ADDR {
+            self.selected = bytes[0];
+            return Ok(());
+        }
+        let registers = self.registers(addr)?;
+        for pair in bytes.chunks(2) {
+            if let [reg_addr, value] = pair {
+                registers[*reg_addr as usize] = *value;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl WriteRead for MuxBus {
+    type Error = ();
+
+    fn write_read(&mut self, addr: u8, bytes: &[u8], buffer: &mut [u8]) -> Result<(), Self::Error> {
+        let registers = self.registers(addr)?;
+        let start = bytes[0] as usize;
+        buffer.copy_from_slice(&registers[start..start + buffer.len()]);
+        Ok(())
+    }
+}
+
+#[test]
+fn readings_are_tagged_by_channel() {
+    let bus = RefCell::new(MuxBus::new(&[0, 3, 7]));
+    let mut delay = NoDelay;
+    let mut sensors =
+        MuxedSensors::init(&bus, TCA9548A_ADDR, I2CAddress::Primary, &mut delay).unwrap();
+    assert_eq!(sensors.channels().collect::<Vec<_>>(), [0, 3, 7]);
+
+    let settings = SettingsBuilder::new()
+        .with_temperature_oversampling(OversamplingSetting::OS8x)
+        .with_pressure_oversampling(OversamplingSetting::OS8x)
+        .with_run_gas(false)
+        .build();
+    sensors.set_sensor_settings(&mut delay, settings).unwrap();
+    let readings = sensors.measure(&mut delay).unwrap();
+
+    let channels: Vec<_> = readings
+        .iter()
+        .enumerate()
+        .filter_map(|(index, reading)| reading.as_ref().map(|reading| (index, reading.channel)))
+        .collect();
+    assert_eq!(channels, [(0, 0), (3, 3), (7, 7)]);
+
+    // Every populated channel was configured individually
+    for registers in bus.borrow().channels.iter().flatten() {
+        assert_eq!(registers[0x74] >> 5, OversamplingSetting::OS8x as u8);
+    }
+}
+
+#[test]
+fn init_fails_without_multiplexer() {
+    struct EmptyBus;
+
+    impl Write for EmptyBus {
+        type Error = ();
+
+        fn write(&mut self, _addr: u8, _bytes: &[u8]) -> Result<(), Self::Error> {
+            Err(())
+        }
+    }
+
+    impl WriteRead for EmptyBus {
+        type Error = ();
+
+        fn write_read(&mut self, _addr: u8, _bytes: &[u8], _buf: &mut [u8]) -> Result<(), ()> {
+            Err(())
+        }
+    }
+
+    let bus = RefCell::new(EmptyBus);
+    assert!(MuxedSensors::init(&bus, TCA9548A_ADDR, I2CAddress::Primary, &mut NoDelay).is_err());
+}
+
+#[test]
+fn init_fails_on_errors_of_present_sensors() {
+    let mut bus = MuxBus::new(&[0, 2]);
+    // Zeroed par_t1 on channel 2
+    if let Some(registers) = bus.channels[2].as_mut() {
+        registers[0xe9] = 0;
+        registers[0xea] = 0;
+    }
+    let bus = RefCell::new(bus);
+
+    let result = MuxedSensors::init(&bus, TCA9548A_ADDR, I2CAddress::Primary, &mut NoDelay);
+    assert!(matches!(
+        result,
+        Err(bme680::Error::InvalidCalibration("par_t1"))
+    ));
+}
@@ -0,0 +1,181 @@
+//! Two sensors at the standard addresses sharing one I²C bus.
+
+use bme680::{Bme680, I2CAddress, OversamplingSetting, PowerMode, SettingsBuilder, SharedI2c};
+use core::cell::RefCell;
+use embedded_hal::blocking::i2c::{Write, WriteRead};
+
+mod common;
+
+use common::NoDelay;
+
+/// Bus with a sensor at each of the standard addresses
+struct Bus {
+    primary: [u8; 256],
+    secondary: [u8; 256],
+}
+
+impl Bus {
+    fn new() -> Bus {
+        Bus {
+            primary: common::registers(),
+            secondary: common::registers(),
+        }
+    }
+
+    fn registers(&mut self, addr: u8) -> Result<&mut [u8; 256], ()> {
+        match addr {
+            0x76 => Ok(&mut self.primary),
+            0x77 => Ok(&mut self.secondary),
+            _ => Err(()),
+        }
+    }
+
+    fn write(&mut self, addr: u8, bytes: &[u8]) -> Result<(), ()> {
+        let registers = self.registers(addr)?;
+        for pair in bytes.chunks(2) {
+            if let [reg_addr, value] = pair {
+                registers[*reg_addr as usize] = *value;
+            }
+        }
+        Ok(())
+    }
+
+    fn write_read(&mut self, addr: u8, bytes: &[u8], buffer: &mut [u8]) -> Result<(), ()> {
+        let registers = self.registers(addr)?;
+        let start = bytes[0] as usize;
+        buffer.copy_from_slice(&registers[start..start + buffer.len()]);
+        Ok(())
+    }
+}
+
+impl Write for Bus {
+    type Error = ();
+
+    fn write(&mut self, addr: u8, bytes: &[u8]) -> Result<(), Self::Error> {
+        Bus::write(self, addr, bytes)
+    }
+}
+
+impl WriteRead for Bus {
+    type Error = ();
+
+    fn write_read(&mut self, addr: u8, bytes: &[u8], buffer: &mut [u8]) -> Result<(), Self::Error> {
+        Bus::write_read(self, addr, bytes, buffer)
+    }
+}
+
+/// Mode bits of `ctrl_meas` selecting forced mode
+const FORCED_MODE: u8 = 0x01;
+
+#[test]
+fn two_sensors_measure_alternately() {
+    let bus = RefCell::new(Bus::new());
+    let mut delay = NoDelay;
+    let mut first = Bme680::init(SharedI2c::new(&bus), &mut delay, I2CAddress::Primary).unwrap();
+    let mut second = Bme680::init(SharedI2c::new(&bus), &mut delay, I2CAddress::Secondary).unwrap();
+
+    let settings = |os_temp| {
+        SettingsBuilder::new()
+            .with_temperature_oversampling(os_temp)
+            .with_pressure_oversampling(os_temp)
+            .with_run_gas(false)
+            .build()
+    };
+    first
+        .set_sensor_settings(&mut delay, settings(OversamplingSetting::OS2x))
+        .unwrap();
+    second
+        .set_sensor_settings(&mut delay, settings(OversamplingSetting::OS8x))
+        .unwrap();
+
+    for _ in 0..2 {
+        first
+            .set_sensor_mode(&mut delay, PowerMode::ForcedMode)
+            .unwrap();
+        first.get_sensor_data(&mut delay).unwrap();
+        second
+            .set_sensor_mode(&mut delay, PowerMode::ForcedMode)
+            .unwrap();
+        second.get_sensor_data(&mut delay).unwrap();
+    }
+
+    // Temperature oversampling and mode bits of ctrl_meas
+    let bus = bus.borrow();
+    assert_eq!(bus.primary[0x74] >> 5, OversamplingSetting::OS2x as u8);
+    assert_eq!(bus.primary[0x74] & 0x03, FORCED_MODE);
+    assert_eq!(bus.secondary[0x74] >> 5, OversamplingSetting::OS8x as u8);
+    assert_eq!(bus.secondary[0x74] & 0x03, FORCED_MODE);
+}
+
+#[cfg(feature = "embedded-hal-1")]
+mod ref_cell_device {
+    use super::*;
+    use bme680::eh1::{DelayAdapter, I2cAdapter};
+    use embedded_hal_1::delay::DelayNs;
+    use embedded_hal_1::i2c::{ErrorKind, ErrorType, I2c, Operation};
+    use embedded_hal_bus::i2c::RefCellDevice;
+
+    #[derive(Debug)]
+    pub(super) struct BusError;
+
+    impl embedded_hal_1::i2c::Error for BusError {
+        fn kind(&self) -> ErrorKind {
+            ErrorKind::Other
+        }
+    }
+
+    impl ErrorType for Bus {
+        type Error = BusError;
+    }
+
+    impl I2c for Bus {
+        fn transaction(
+            &mut self,
+            addr: u8,
+            operations: &mut [Operation<'_>],
+        ) -> Result<(), Self::Error> {
+            match operations {
+                [Operation::Write(bytes)] => Bus::write(self, addr, bytes),
+                [Operation::Write(bytes), Operation::Read(buffer)] => {
+                    Bus::write_read(self, addr, bytes, buffer)
+                }
+                _ => Err(()),
+            }
+            .map_err(|_| BusError)
+        }
+    }
+
+    struct NoDelayNs;
+
+    impl DelayNs for NoDelayNs {
+        fn delay_ns(&mut self, _ns: u32) {}
+    }
+
+    #[test]
+    fn two_sensors_on_ref_cell_device() {
+        let bus = RefCell::new(Bus::new());
+        let mut delay = DelayAdapter::new(NoDelayNs);
+        let mut first = Bme680::init(
+            I2cAdapter::new(RefCellDevice::new(&bus)),
+            &mut delay,
+            I2CAddress::Primary,
+        )
+        .unwrap();
+        let mut second = Bme680::init(
+            I2cAdapter::new(RefCellDevice::new(&bus)),
+            &mut delay,
+            I2CAddress::Secondary,
+        )
+        .unwrap();
+
+        first
+            .set_sensor_mode(&mut delay, PowerMode::ForcedMode)
+            .unwrap();
+        assert_eq!(bus.borrow().primary[0x74], FORCED_MODE);
+        assert_eq!(bus.borrow().secondary[0x74], 0);
+        second
+            .set_sensor_mode(&mut delay, PowerMode::ForcedMode)
+            .unwrap();
+        assert_eq!(bus.borrow().secondary[0x74], FORCED_MODE);
+    }
+}
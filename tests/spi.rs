@@ -0,0 +1,284 @@
+//! Checks the SPI transactions issued by the driver, including the memory page handling.
+
+use bme680::{Bme680, OversamplingSetting, PowerMode, SettingsBuilder};
+use embedded_hal::blocking::spi::{Transfer, Write};
+use embedded_hal::digital::v2::OutputPin;
+use std::cell::RefCell;
+use std::rc::Rc;
+
+mod common;
+
+use common::NoDelay;
+
+/// `spi_mem_page` bit of the status register, selecting registers `0x00..=0x7F`
+const MEM_PAGE1: u8 = 0x10;
+
+/// Sensor in SPI mode recording the bytes sent in each chip select frame
+struct Sensor {
+    registers: [u8; 256],
+    /// Bytes sent while chip select was asserted, one entry per frame
+    frames: Vec<Vec<u8>>,
+    /// Register writes with the register address resolved using the selected page
+    writes: Vec<(u8, u8)>,
+    /// Register written or read next within the current frame
+    reg_addr: Option<u8>,
+    reading: bool,
+}
+
+impl Sensor {
+    fn new() -> Sensor {
+        Sensor {
+            registers: common::registers(),
+            frames: Vec::new(),
+            writes: Vec::new(),
+            reg_addr: None,
+            reading: false,
+        }
+    }
+
+    /// Register addressed by the 7-bit SPI address within the selected memory page
+    fn resolve(&self, spi_addr: u8) -> u8 {
+        // The status register is available in both pages
+        if spi_addr == 0x73 || self.registers[0x73] & MEM_PAGE1 != 0 {
+            spi_addr
+        } else {
+            spi_addr | 0x80
+        }
+    }
+
+    fn receive(&mut self, byte: u8) -> u8 {
+        self.frames.last_mut().unwrap().push(byte);
+        match self.reg_addr {
+            None if byte & 0x80 != 0 => {
+                self.reg_addr = Some(self.resolve(byte & 0x7f));
+                self.reading = true;
+                0
+            }
+            None => {
+                self.reg_addr = Some(self.resolve(byte));
+                0
+            }
+            Some(reg_addr) if self.reading => {
+                self.reg_addr = Some(reg_addr + 1);
+                self.registers[reg_addr as usize]
+            }
+            Some(reg_addr) => {
+                self.write(reg_addr, byte);
+                self.reg_addr = None;
+                0
+            }
+        }
+    }
+
+    fn write(&mut self, reg_addr: u8, value: u8) {
+        self.writes.push((reg_addr, value));
+        self.registers[reg_addr as usize] = value;
+        match (reg_addr, value) {
+            // Soft reset selects memory page 0
+            (0xe0, 0xb6) => self.registers[0x73] = 0,
+            // The forced measurement completes immediately
+            (0x74, value) if value & 0x03 == 0x01 => self.registers[0x74] &= !0x03,
+            _ => {}
+        }
+    }
+}
+
+#[derive(Clone)]
+struct Spi(Rc<RefCell<Sensor>>);
+
+impl Write<u8> for Spi {
+    type Error = ();
+
+    fn write(&mut self, words: &[u8]) -> Result<(), Self::Error> {
+        let mut sensor = self.0.borrow_mut();
+        for word in words {
+            sensor.receive(*word);
+        }
+        Ok(())
+    }
+}
+
+impl Transfer<u8> for Spi {
+    type Error = ();
+
+    fn transfer<'w>(&mut self, words: &'w mut [u8]) -> Result<&'w [u8], Self::Error> {
+        let mut sensor = self.0.borrow_mut();
+        for word in words.iter_mut() {
+            *word = sensor.receive(*word);
+        }
+        Ok(words)
+    }
+}
+
+struct ChipSelect(Rc<RefCell<Sensor>>);
+
+impl OutputPin for ChipSelect {
+    type Error = ();
+
+    fn set_low(&mut self) -> Result<(), Self::Error> {
+        let mut sensor = self.0.borrow_mut();
+        sensor.frames.push(Vec::new());
+        sensor.reg_addr = None;
+        sensor.reading = false;
+        Ok(())
+    }
+
+    fn set_high(&mut self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+}
+
+type Driver = Bme680<bme680::SpiInterface<Spi, ChipSelect>, NoDelay>;
+
+fn init_spi() -> (Driver, Rc<RefCell<Sensor>>) {
+    let sensor = Rc::new(RefCell::new(Sensor::new()));
+    let dev = Bme680::init_spi(
+        Spi(sensor.clone()),
+        ChipSelect(sensor.clone()),
+        &mut NoDelay,
+    )
+    .unwrap();
+    (dev, sensor)
+}
+
+/// Frame reading `len` registers starting at the 7-bit address `spi_addr`
+fn read(spi_addr: u8, len: usize) -> Vec<u8> {
+    let mut frame = vec![spi_addr | 0x80];
+    frame.resize(len + 1, 0);
+    frame
+}
+
+/// Frame selecting the memory page
+fn select_page(page1: bool) -> Vec<u8> {
+    vec![0x73, if page1 { MEM_PAGE1 } else { 0 }]
+}
+
+#[test]
+fn init_and_forced_measurement_switch_pages() {
+    let (mut dev, sensor) = init_spi();
+    let settings = SettingsBuilder::new()
+        .with_temperature_oversampling(OversamplingSetting::OS2x)
+        .with_pressure_oversampling(OversamplingSetting::OS2x)
+        .build();
+    dev.set_sensor_settings(&mut NoDelay, settings).unwrap();
+    dev.set_sensor_mode(&mut NoDelay, PowerMode::ForcedMode)
+        .unwrap();
+    let (data, _) = dev.get_sensor_data(&mut NoDelay).unwrap();
+    assert!(data.temperature_celsius() > 0.0);
+
+    let expected: Vec<Vec<u8>> = vec![
+        // Soft reset in page 0, after which the page is known to be 0
+        select_page(false),
+        vec![0x60, 0xb6],
+        // Chip id, variant id and calibration data in page 0
+        read(0x50, 1),
+        read(0x70, 1),
+        read(0x09, 24),
+        read(0x61, 15),
+        // res_heat_range, res_heat_val and range_sw_err in page 1
+        select_page(true),
+        read(0x02, 1),
+        read(0x00, 1),
+        read(0x04, 1),
+        // set_sensor_settings reads the mode and the configuration, then writes ctrl_meas
+        read(0x74, 1),
+        read(0x70, 6),
+        vec![0x74, 0x48],
+        // Forced mode and the field data
+        read(0x74, 1),
+        vec![0x74, 0x49],
+        read(0x1d, 15),
+    ];
+    let sensor = sensor.borrow();
+    assert_eq!(sensor.frames, expected);
+    // Resolved using the selected page, so the reset did not hit res_heat_6 at 0x60 of page 1
+    assert_eq!(
+        sensor.writes,
+        [
+            (0x73, 0x00),
+            (0xe0, 0xb6),
+            (0x73, MEM_PAGE1),
+            (0x74, 0x48),
+            (0x74, 0x49)
+        ]
+    );
+}
+
+#[test]
+fn selected_page_is_not_selected_again() {
+    let (mut dev, sensor) = init_spi();
+    sensor.borrow_mut().frames.clear();
+
+    dev.set_sensor_mode(&mut NoDelay, PowerMode::ForcedMode)
+        .unwrap();
+    // Bit 7 of the address is set for reads and cleared for writes
+    assert_eq!(sensor.borrow().frames, [vec![0xf4, 0x00], vec![0x74, 0x01]]);
+}
+
+#[test]
+fn burst_across_page_boundary_is_split() {
+    use bme680::Interface;
+
+    let (dev, sensor) = init_spi();
+    let mut interface = dev
+        .release(&mut NoDelay)
+        .map(|(spi, cs)| bme680::SpiInterface::new(spi, cs))
+        .unwrap();
+    sensor.borrow_mut().frames.clear();
+
+    // The page is unknown to the new interface, so it is selected even if unchanged
+    interface
+        .write_registers(&[(0x74, 0x00), (0x75, 0x08), (0xe0, 0xb6), (0x72, 0x01)])
+        .unwrap();
+    let sensor = sensor.borrow();
+    // Page 0 registers are written only while page 0 is selected and vice versa
+    assert_eq!(
+        sensor.writes[sensor.writes.len() - 7..],
+        [
+            (0x73, MEM_PAGE1),
+            (0x74, 0x00),
+            (0x75, 0x08),
+            (0x73, 0x00),
+            (0xe0, 0xb6),
+            (0x73, MEM_PAGE1),
+            (0x72, 0x01),
+        ]
+    );
+    assert_eq!(
+        sensor.frames,
+        [
+            select_page(true),
+            vec![0x74, 0x00, 0x75, 0x08],
+            select_page(false),
+            vec![0x60, 0xb6],
+            select_page(true),
+            vec![0x72, 0x01],
+        ]
+    );
+}
+
+#[test]
+fn three_wire_mode_is_enabled_before_reading() {
+    let sensor = Rc::new(RefCell::new(Sensor::new()));
+    Bme680::init_spi_3wire(
+        Spi(sensor.clone()),
+        ChipSelect(sensor.clone()),
+        &mut NoDelay,
+    )
+    .unwrap();
+
+    let sensor = sensor.borrow();
+    assert_eq!(
+        sensor.frames[..5],
+        [
+            select_page(false),
+            vec![0x60, 0xb6],
+            select_page(true),
+            // spi_3w_en of register 0x75
+            vec![0x75, 0x01],
+            select_page(false),
+        ]
+    );
+    assert_eq!(sensor.writes[2..4], [(0x73, MEM_PAGE1), (0x75, 0x01)]);
+    assert_eq!(sensor.frames[5], read(0x50, 1));
+}
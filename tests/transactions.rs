@@ -0,0 +1,1345 @@
+//! Checks the I²C transactions issued by the driver against a recorded transaction log.
+
+use bme680::{Bme680, I2CAddress, I2cInterface, PowerMode};
+use embedded_hal::blocking::delay::DelayMs;
+use embedded_hal::blocking::i2c::{Write, WriteRead};
+use std::cell::RefCell;
+use std::ops::Range;
+use std::rc::Rc;
+
+mod common;
+
+use common::NoDelay;
+
+#[derive(Debug, PartialEq)]
+enum Transaction {
+    Write { addr: u8, bytes: Vec<u8> },
+    WriteRead { addr: u8, reg_addr: u8, len: usize },
+}
+
+type Log = Rc<RefCell<Vec<Transaction>>>;
+
+/// Register map of a sensor recording every bus transaction
+struct RecordingI2c {
+    /// Address the sensor responds at
+    addr: u8,
+    registers: [u8; 256],
+    log: Log,
+}
+
+impl RecordingI2c {
+    fn new() -> RecordingI2c {
+        RecordingI2c {
+            addr: 0x76,
+            registers: common::registers(),
+            log: Log::default(),
+        }
+    }
+}
+
+impl Write for RecordingI2c {
+    type Error = ();
+
+    fn write(&mut self, addr: u8, bytes: &[u8]) -> Result<(), Self::Error> {
+        if addr != self.addr {
+            return Err(());
+        }
+        for pair in bytes.chunks(2) {
+            if let [reg_addr, value] = pair {
+                self.registers[*reg_addr as usize] = *value;
+                // Forced mode measures using the heater set-point selected by nb_conv
+                if *reg_addr == 0x74 && value & 0x03 == 0x01 {
+                    self.registers[0x1d] = 0x80 | (self.registers[0x71] & 0x0f);
+                }
+            }
+        }
+        self.log.borrow_mut().push(Transaction::Write {
+            addr,
+            bytes: bytes.to_vec(),
+        });
+        Ok(())
+    }
+}
+
+impl WriteRead for RecordingI2c {
+    type Error = ();
+
+    fn write_read(&mut self, addr: u8, bytes: &[u8], buffer: &mut [u8]) -> Result<(), Self::Error> {
+        if addr != self.addr {
+            return Err(());
+        }
+        let start = bytes[0] as usize;
+        buffer.copy_from_slice(&self.registers[start..start + buffer.len()]);
+        // The forced measurement has completed once its data is read, so the sensor is
+        // back in sleep mode
+        if bytes[0] == 0x1d {
+            self.registers[0x74] &= !0x03;
+        }
+        self.log.borrow_mut().push(Transaction::WriteRead {
+            addr,
+            reg_addr: bytes[0],
+            len: buffer.len(),
+        });
+        Ok(())
+    }
+}
+
+/// Sums up the requested delays instead of blocking
+#[derive(Default)]
+struct CountingDelay(u32);
+
+impl DelayMs<u8> for CountingDelay {
+    fn delay_ms(&mut self, ms: u8) {
+        self.0 += ms as u32;
+    }
+}
+
+fn write_read(reg_addr: u8, len: usize) -> Transaction {
+    Transaction::WriteRead {
+        addr: 0x76,
+        reg_addr,
+        len,
+    }
+}
+
+/// Initializes the driver at the primary address, returning it with the transaction log
+fn init_recording<D: DelayMs<u8>>(
+    i2c: RecordingI2c,
+    delay: &mut D,
+) -> (Bme680<I2cInterface<RecordingI2c>, D>, Log) {
+    let log = i2c.log.clone();
+    let dev = Bme680::init(i2c, delay, I2CAddress::Primary).unwrap();
+    (dev, log)
+}
+
+/// Bytes of the logged write transactions
+fn writes(log: &Log) -> Vec<Vec<u8>> {
+    log.borrow()
+        .iter()
+        .filter_map(|transaction| match transaction {
+            Transaction::Write { bytes, .. } => Some(bytes.clone()),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Number of logged transactions equal to `transaction`
+fn count(log: &Log, transaction: &Transaction) -> usize {
+    log.borrow()
+        .iter()
+        .filter(|logged| *logged == transaction)
+        .count()
+}
+
+#[test]
+fn init_reads_chip_id_and_calibration_in_single_transactions() {
+    let mut delay = NoDelay;
+    let (_, log) = init_recording(RecordingI2c::new(), &mut delay);
+
+    assert_eq!(
+        *log.borrow(),
+        vec![
+            Transaction::Write {
+                addr: 0x76,
+                bytes: vec![0xe0, 0xb6],
+            },
+            write_read(0xd0, 1),
+            write_read(0xf0, 1),
+            write_read(0x89, 24),
+            write_read(0xe1, 15),
+            write_read(0x02, 1),
+            write_read(0x00, 1),
+            write_read(0x04, 1),
+        ]
+    );
+}
+
+#[test]
+fn field_data_is_read_in_a_single_transaction() {
+    let mut delay = NoDelay;
+    let (mut dev, log) = init_recording(RecordingI2c::new(), &mut delay);
+    dev.set_sensor_mode(&mut delay, PowerMode::ForcedMode)
+        .unwrap();
+    log.borrow_mut().clear();
+
+    dev.get_sensor_data(&mut delay).unwrap();
+
+    assert_eq!(*log.borrow(), [write_read(0x1d, 15)]);
+}
+
+#[test]
+fn sensor_settings_are_written_in_a_single_burst() {
+    use bme680::{IIRFilterSize, OversamplingSetting, SettingsBuilder};
+    use core::time::Duration;
+
+    let mut delay = NoDelay;
+    let (mut dev, log) = init_recording(RecordingI2c::new(), &mut delay);
+    let settings = SettingsBuilder::new()
+        .with_humidity_oversampling(OversamplingSetting::OS2x)
+        .with_pressure_oversampling(OversamplingSetting::OS4x)
+        .with_temperature_oversampling(OversamplingSetting::OS8x)
+        .with_temperature_filter(IIRFilterSize::Size3)
+        .with_gas_measurement(Duration::from_millis(1500), 320, 25)
+        .with_run_gas(true)
+        .build();
+    log.borrow_mut().clear();
+
+    dev.set_sensor_settings(&mut delay, settings).unwrap();
+
+    let writes = writes(&log);
+    assert_eq!(writes.len(), 1);
+    let registers: Vec<u8> = writes[0].chunks(2).map(|pair| pair[0]).collect();
+    assert_eq!(registers, [0x5a, 0x64, 0x75, 0x74, 0x72, 0x71]);
+}
+
+#[test]
+fn release_puts_sensor_to_sleep_and_returns_bus() {
+    let mut delay = NoDelay;
+    let mut i2c = RecordingI2c::new();
+    let mut dev = Bme680::init_borrowed(&mut i2c, &mut delay, I2CAddress::Primary).unwrap();
+    dev.set_sensor_mode(&mut delay, PowerMode::ForcedMode)
+        .unwrap();
+    dev.release(&mut delay).unwrap();
+
+    // Forced mode bits of ctrl_meas are cleared again
+    assert_eq!(i2c.registers[0x74] & 0x03, 0);
+    let dev = Bme680::init(i2c, &mut delay, I2CAddress::Primary).unwrap();
+    let i2c = dev.release(&mut delay).unwrap();
+    assert_eq!(i2c.registers[0x74] & 0x03, 0);
+}
+
+#[test]
+fn detects_sensor_at_secondary_address() {
+    let mut delay = NoDelay;
+    let mut i2c = RecordingI2c::new();
+    i2c.addr = 0x77;
+
+    assert_eq!(
+        bme680::scan(&mut i2c)
+            .map(|address| address.addr())
+            .collect::<Vec<_>>(),
+        [0x77]
+    );
+    let dev = Bme680::init_detect(i2c, &mut delay).unwrap();
+    assert_eq!(dev.address().addr(), 0x77);
+}
+
+#[test]
+fn detection_fails_without_sensor() {
+    let mut delay = NoDelay;
+    let mut i2c = RecordingI2c::new();
+    i2c.addr = 0x42;
+
+    assert_eq!(bme680::scan(&mut i2c).count(), 0);
+    assert!(matches!(
+        Bme680::init_detect(i2c, &mut delay),
+        Err(bme680::Error::DeviceNotFound)
+    ));
+}
+
+#[test]
+fn heater_profile_configures_all_set_points() {
+    use bme680::HeaterStep;
+    use core::time::Duration;
+
+    let mut delay = NoDelay;
+    let mut i2c = RecordingI2c::new();
+    // run_gas enabled
+    i2c.registers[0x71] = 0x10;
+    // gas index 7 in field 0
+    i2c.registers[0x1d] = 0x87;
+    let (mut dev, log) = init_recording(i2c, &mut delay);
+    let steps: Vec<_> = (0..10)
+        .map(|step| HeaterStep::new(200 + step * 20, Duration::from_millis(100)))
+        .collect();
+    log.borrow_mut().clear();
+
+    dev.set_heater_profile(&mut delay, 25, &steps).unwrap();
+    dev.select_heater_step(&mut delay, 7).unwrap();
+
+    let writes = writes(&log);
+    assert_eq!(writes.len(), 2);
+    let registers: Vec<u8> = writes[0].chunks(2).map(|pair| pair[0]).collect();
+    let mut expected: Vec<u8> = Vec::new();
+    for step in 0..10 {
+        expected.extend_from_slice(&[0x5a + step, 0x64 + step]);
+    }
+    assert_eq!(registers, expected);
+    // Durations of 100 ms are encoded as 0x59
+    assert!(writes[0]
+        .chunks(2)
+        .skip(1)
+        .step_by(2)
+        .all(|pair| pair[1] == 0x59));
+    // nb_conv is set while run_gas stays enabled
+    assert_eq!(writes[1], [0x71, 0x17]);
+
+    dev.set_sensor_mode(&mut delay, PowerMode::ForcedMode)
+        .unwrap();
+    let (data, _) = dev.get_sensor_data(&mut delay).unwrap();
+    assert_eq!(data.gas_index(), 7);
+}
+
+#[test]
+fn heater_profile_rejects_more_than_ten_steps() {
+    use bme680::HeaterStep;
+    use core::time::Duration;
+
+    let mut delay = NoDelay;
+    let (mut dev, _) = init_recording(RecordingI2c::new(), &mut delay);
+    let steps = [HeaterStep::new(300, Duration::from_millis(100)); 11];
+
+    assert!(matches!(
+        dev.set_heater_profile(&mut delay, 25, &steps),
+        Err(bme680::Error::InvalidLength)
+    ));
+    assert!(matches!(
+        dev.select_heater_step(&mut delay, 10),
+        Err(bme680::Error::BoundaryCheckFailure(_))
+    ));
+}
+
+#[test]
+fn sequencer_scans_each_heater_step() {
+    use bme680::{HeaterSequencer, HeaterStep, OversamplingSetting, SettingsBuilder};
+    use core::time::Duration;
+
+    let mut delay = CountingDelay::default();
+    let (dev, _) = init_recording(RecordingI2c::new(), &mut delay);
+    let settings = SettingsBuilder::new()
+        .with_temperature_oversampling(OversamplingSetting::OS1x)
+        .build();
+    let steps = [
+        HeaterStep::new(200, Duration::from_millis(100)),
+        HeaterStep::new(250, Duration::from_millis(200)),
+        HeaterStep::new(300, Duration::from_millis(300)),
+        HeaterStep::new(350, Duration::from_millis(400)),
+    ];
+    let mut sequencer = HeaterSequencer::new(dev, &mut delay, settings, 25, &steps).unwrap();
+
+    delay.0 = 0;
+    let scan = sequencer.scan(&mut delay).unwrap();
+
+    let gas_indices: Vec<_> = scan
+        .readings()
+        .iter()
+        .map(|data| data.gas_index())
+        .collect();
+    assert_eq!(gas_indices, [0, 1, 2, 3]);
+    // Each step waits for the TPH measurement of 7 ms plus its heating duration
+    assert_eq!(delay.0, 4 * 7 + 100 + 200 + 300 + 400);
+}
+
+#[test]
+fn bme688_variant_enables_gas_measurements_with_its_run_gas_bits() {
+    use bme680::{ChipVariant, DesiredSensorSettings, SettingsBuilder};
+
+    for (variant_id, variant, run_gas) in [
+        (0x00, ChipVariant::Bme680, 0x10),
+        (0x01, ChipVariant::Bme688, 0x20),
+    ]
+    .iter()
+    {
+        let mut delay = NoDelay;
+        let mut i2c = RecordingI2c::new();
+        i2c.registers[0xf0] = *variant_id;
+        let mut dev = Bme680::init_borrowed(&mut i2c, &mut delay, I2CAddress::Primary).unwrap();
+        assert_eq!(dev.chip_variant(), *variant);
+
+        let settings = SettingsBuilder::new().with_run_gas(true).build();
+        dev.set_sensor_settings(&mut delay, settings).unwrap();
+        let sensor_settings = dev
+            .get_sensor_settings(DesiredSensorSettings::GAS_SENSOR_SEL)
+            .unwrap();
+        assert!(sensor_settings.gas_sett.run_gas_measurement);
+        assert_eq!(i2c.registers[0x71] & 0x30, *run_gas);
+    }
+}
+
+#[test]
+fn bme688_gas_resistance_is_read_from_its_gas_registers() {
+    let mut delay = NoDelay;
+    let mut i2c = RecordingI2c::new();
+    i2c.registers[0xf0] = 0x01;
+    // gas_r of 900 in range 5, valid and heater stable
+    i2c.registers[0x2c] = 0xe1;
+    i2c.registers[0x2d] = 0x35;
+    let (mut dev, log) = init_recording(i2c, &mut delay);
+    dev.set_sensor_mode(&mut delay, PowerMode::ForcedMode)
+        .unwrap();
+    log.borrow_mut().clear();
+
+    let (data, _) = dev.get_sensor_data(&mut delay).unwrap();
+
+    assert_eq!(*log.borrow(), [write_read(0x1d, 17)]);
+    assert_eq!(data.gas_resistance_ohm(), 1_557_400);
+    assert!(data.gas_valid());
+    assert!(data.heat_stable());
+}
+
+#[test]
+fn bme688_parallel_mode_reads_fields_ordered_by_measurement_index() {
+    use bme680::ParallelHeaterStep;
+    use core::time::Duration;
+
+    let mut delay = NoDelay;
+    let mut i2c = RecordingI2c::new();
+    i2c.registers[0xf0] = 0x01;
+    // New data in all fields, the second holding the oldest and the first the newest
+    // measurement, with the measurement index wrapping around
+    for (field, meas_index) in [(0x1d, 0x00), (0x2e, 0xfe), (0x3f, 0xff)].iter() {
+        i2c.registers[*field] = 0x80;
+        i2c.registers[*field + 1] = *meas_index;
+    }
+    let log = i2c.log.clone();
+    let mut dev = Bme680::init_borrowed(&mut i2c, &mut delay, I2CAddress::Primary).unwrap();
+    let steps = [
+        ParallelHeaterStep::new(320, 5),
+        ParallelHeaterStep::new(100, 2),
+        ParallelHeaterStep::new(200, 10),
+    ];
+    log.borrow_mut().clear();
+
+    dev.set_parallel_heater_profile(&mut delay, 25, Duration::from_millis(140), &steps)
+        .unwrap();
+    dev.set_sensor_mode(&mut delay, PowerMode::ParallelMode)
+        .unwrap();
+    let fields = dev.get_all_field_data().unwrap();
+
+    let meas_indices: Vec<_> = fields
+        .readings()
+        .iter()
+        .map(|data| data.meas_index())
+        .collect();
+    assert_eq!(meas_indices, [0xfe, 0xff, 0x00]);
+    assert_eq!(log.borrow().last(), Some(&write_read(0x1d, 51)));
+    let writes = writes(&log);
+    let profile: Vec<_> = writes[0].chunks(2).map(|pair| (pair[0], pair[1])).collect();
+    assert_eq!(profile[1], (0x64, 5));
+    assert_eq!(profile[3], (0x65, 2));
+    assert_eq!(profile[5], (0x66, 10));
+    // 140 ms in steps of 0.477 ms, with a factor of 4^2
+    assert_eq!(profile[6], (0x6e, 0x92));
+    // Gas measurements enabled for three heater steps
+    assert_eq!(profile[7], (0x71, 0x23));
+    // Parallel mode
+    assert_eq!(writes[1], [0x74, 0x02]);
+}
+
+#[test]
+fn bme680_does_not_support_parallel_mode() {
+    let mut delay = NoDelay;
+    let (mut dev, _) = init_recording(RecordingI2c::new(), &mut delay);
+
+    assert!(matches!(
+        dev.set_sensor_mode(&mut delay, PowerMode::ParallelMode),
+        Err(bme680::Error::DefinePwrMode)
+    ));
+}
+
+#[test]
+fn bme688_sequential_mode_reader_skips_fields_already_read() {
+    use bme680::{FieldDataReader, HeaterStep};
+    use core::time::Duration;
+
+    let mut delay = NoDelay;
+    let mut i2c = RecordingI2c::new();
+    i2c.registers[0xf0] = 0x01;
+    for (field, meas_index) in [(0x1d, 1), (0x2e, 2), (0x3f, 3)].iter() {
+        i2c.registers[*field] = 0x80;
+        i2c.registers[*field + 1] = *meas_index;
+    }
+    let log = i2c.log.clone();
+    let mut dev = Bme680::init_borrowed(&mut i2c, &mut delay, I2CAddress::Primary).unwrap();
+    let steps = [
+        HeaterStep::new(200, Duration::from_millis(100)),
+        HeaterStep::new(300, Duration::from_millis(100)),
+    ];
+    log.borrow_mut().clear();
+
+    dev.set_sequential_heater_profile(&mut delay, 25, &steps)
+        .unwrap();
+    dev.set_sensor_mode(&mut delay, PowerMode::SequentialMode)
+        .unwrap();
+    let mut reader = FieldDataReader::new();
+    let first: Vec<_> = reader
+        .read(&mut dev)
+        .unwrap()
+        .readings()
+        .iter()
+        .map(|data| data.meas_index())
+        .collect();
+    assert_eq!(first, [1, 2, 3]);
+    assert!(reader.read(&mut dev).unwrap().readings().is_empty());
+
+    let writes = writes(&log);
+    // Gas measurements enabled for two heater steps
+    assert_eq!(writes[0][8..], [0x71, 0x22]);
+    // Sequential mode
+    assert_eq!(writes[1], [0x74, 0x03]);
+}
+
+#[test]
+fn field_data_reader_returns_only_newer_measurements() {
+    use bme680::FieldDataReader;
+
+    let mut delay = NoDelay;
+    let mut i2c = RecordingI2c::new();
+    i2c.registers[0xf0] = 0x01;
+    for (field, meas_index) in [(0x1d, 0xfe), (0x2e, 0xff), (0x3f, 0x00)].iter() {
+        i2c.registers[*field] = 0x80;
+        i2c.registers[*field + 1] = *meas_index;
+    }
+    let mut reader = FieldDataReader::new();
+    {
+        let mut dev = Bme680::init_borrowed(&mut i2c, &mut delay, I2CAddress::Primary).unwrap();
+        assert_eq!(reader.read(&mut dev).unwrap().readings().len(), 3);
+    }
+
+    // The oldest field is overwritten by the next measurement
+    i2c.registers[0x1e] = 0x01;
+    let mut dev = Bme680::init_borrowed(&mut i2c, &mut delay, I2CAddress::Primary).unwrap();
+    let new: Vec<_> = reader
+        .read(&mut dev)
+        .unwrap()
+        .readings()
+        .iter()
+        .map(|data| data.meas_index())
+        .collect();
+    assert_eq!(new, [0x01]);
+}
+
+#[test]
+fn sensor_settings_decode_heater_set_points() {
+    use bme680::{DesiredSensorSettings, HeaterStep, SettingsBuilder};
+    use core::time::Duration;
+
+    let mut delay = NoDelay;
+    let (mut dev, _) = init_recording(RecordingI2c::new(), &mut delay);
+    let steps: Vec<_> = (0..10)
+        .map(|step| HeaterStep::new(200 + step * 20, Duration::from_millis(50 + step as u64)))
+        .collect();
+    dev.set_heater_profile(&mut delay, 25, &steps).unwrap();
+    let settings = SettingsBuilder::new()
+        .with_gas_measurement(Duration::from_millis(1500), 320, 25)
+        .with_run_gas(true)
+        .build();
+    dev.set_sensor_settings(&mut delay, settings).unwrap();
+
+    let gas_sett = dev
+        .get_sensor_settings(DesiredSensorSettings::GAS_SENSOR_SEL)
+        .unwrap()
+        .gas_sett;
+
+    // 1500 ms is truncated to 23 * 64 ms
+    assert_eq!(gas_sett.heatr_dur, Some(Duration::from_millis(1472)));
+    let set_point = gas_sett.heater_profile[0];
+    assert_eq!(set_point.duration, Duration::from_millis(1472));
+    assert_eq!(set_point.duration_error, Duration::from_millis(63));
+    let temperature = gas_sett.heatr_temp.unwrap();
+    assert_eq!(temperature, set_point.temperature);
+    assert!((temperature as i32 - 320).abs() <= set_point.temperature_error as i32);
+
+    for (step, set_point) in steps.iter().zip(gas_sett.heater_profile.iter()).skip(1) {
+        assert_eq!(set_point.duration, step.duration);
+        assert_eq!(set_point.duration_error, Duration::from_millis(0));
+        assert!(
+            (set_point.temperature as i32 - step.temperature as i32).abs()
+                <= set_point.temperature_error as i32
+        );
+    }
+}
+
+/// Writes raw ADC values to field 0
+fn set_field_adc(
+    registers: &mut [u8; 256],
+    adc_temp: u32,
+    adc_pres: u32,
+    adc_hum: u16,
+    adc_gas: u16,
+    gas_range: u8,
+) {
+    registers[0x1f..0x22].copy_from_slice(&[
+        (adc_pres >> 12) as u8,
+        (adc_pres >> 4) as u8,
+        (adc_pres << 4) as u8,
+    ]);
+    registers[0x22..0x25].copy_from_slice(&[
+        (adc_temp >> 12) as u8,
+        (adc_temp >> 4) as u8,
+        (adc_temp << 4) as u8,
+    ]);
+    registers[0x25..0x27].copy_from_slice(&adc_hum.to_be_bytes());
+    // Gas registers of the BME680 and BME688, with gas valid and heat stable set
+    let gas = [
+        (adc_gas >> 2) as u8,
+        (adc_gas << 6) as u8 | 0x30 | gas_range,
+    ];
+    registers[0x2a..0x2c].copy_from_slice(&gas);
+    registers[0x2c..0x2e].copy_from_slice(&gas);
+}
+
+#[test]
+fn float_and_integer_compensation_agree() {
+    use bme680::Compensation;
+
+    for variant_id in [0, 1] {
+        for (adc_temp, adc_pres, adc_hum, adc_gas, gas_range) in [
+            (400000, 380000, 20000, 300, 3),
+            (450000, 350000, 25000, 512, 5),
+            (500000, 360000, 27000, 700, 7),
+            (550000, 370000, 24000, 1000, 10),
+        ] {
+            let mut delay = NoDelay;
+            let mut i2c = RecordingI2c::new();
+            i2c.registers[0xf0] = variant_id;
+            set_field_adc(
+                &mut i2c.registers,
+                adc_temp,
+                adc_pres,
+                adc_hum,
+                adc_gas,
+                gas_range,
+            );
+            let (mut dev, _) = init_recording(i2c, &mut delay);
+
+            assert_eq!(dev.compensation(), Compensation::Integer);
+            let (integer, _) = dev.get_sensor_data(&mut delay).unwrap();
+            dev.set_compensation(Compensation::Float);
+            let (float, _) = dev.get_sensor_data(&mut delay).unwrap();
+
+            // Absolute accuracy given in the datasheet
+            let context = format!("variant {}: {:?} vs {:?}", variant_id, integer, float);
+            assert!(
+                (integer.temperature_celsius() - float.temperature_celsius()).abs() <= 0.5,
+                "{}",
+                context
+            );
+            assert!(
+                (integer.pressure_hpa() - float.pressure_hpa()).abs() <= 0.6,
+                "{}",
+                context
+            );
+            assert!(
+                (integer.humidity_percent() - float.humidity_percent()).abs() <= 3.0,
+                "{}",
+                context
+            );
+            let gas_deviation =
+                (integer.gas_resistance_ohm() as f32 - float.gas_resistance_ohm() as f32).abs()
+                    / float.gas_resistance_ohm() as f32;
+            assert!(gas_deviation <= 0.01, "{}", context);
+        }
+    }
+}
+
+#[test]
+fn raw_field_data_compensated_offline_matches_sensor_data() {
+    use bme680::{compensate, compensate_float, compensate_i32, Compensation, SettingsBuilder};
+
+    let mut delay = NoDelay;
+    let mut i2c = RecordingI2c::new();
+    set_field_adc(&mut i2c.registers, 500000, 360000, 27000, 700, 7);
+    let (mut dev, _) = init_recording(i2c, &mut delay);
+    let settings = SettingsBuilder::new().with_temperature_offset(-1.5).build();
+    dev.set_sensor_settings(&mut delay, settings).unwrap();
+
+    let (raw, condition) = dev.get_raw_sensor_data(&mut delay).unwrap();
+    assert_eq!(condition, bme680::FieldDataCondition::NewData);
+    assert_eq!(
+        (raw.adc_temp, raw.adc_pres, raw.adc_hum),
+        (500000, 360000, 27000)
+    );
+    assert_eq!((raw.adc_gas_res, raw.gas_range), (700, 7));
+    let calib = dev.calib_data();
+
+    let (data, _) = dev.get_sensor_data(&mut delay).unwrap();
+    let offline = compensate(&calib, &raw, Some(-1.5)).unwrap();
+    assert_eq!(format!("{:?}", offline), format!("{:?}", data));
+    let offline = compensate_i32(&calib, &raw, -150).unwrap();
+    assert_eq!(format!("{:?}", offline), format!("{:?}", data));
+
+    dev.set_compensation(Compensation::Float);
+    let (data, _) = dev.get_sensor_data(&mut delay).unwrap();
+    let offline = compensate_float(&calib, &raw, Some(-1.5)).unwrap();
+    assert_eq!(format!("{:?}", offline), format!("{:?}", data));
+}
+
+#[test]
+fn integer32_compensation_uses_the_integer_temperature_offset() {
+    use bme680::{Compensation, SettingsBuilder};
+
+    let mut delay = NoDelay;
+    let mut i2c = RecordingI2c::new();
+    set_field_adc(&mut i2c.registers, 500000, 360000, 27000, 700, 7);
+    let (mut dev, _) = init_recording(i2c, &mut delay);
+    let settings = SettingsBuilder::new().with_temperature_offset(-1.5).build();
+    dev.set_sensor_settings(&mut delay, settings).unwrap();
+    let (integer, _) = dev.get_sensor_data(&mut delay).unwrap();
+
+    // The floating-point offset is converted if no integer offset is set
+    dev.set_compensation(Compensation::Integer32);
+    let (integer32, _) = dev.get_sensor_data(&mut delay).unwrap();
+    assert_eq!(format!("{:?}", integer32), format!("{:?}", integer));
+
+    let settings = SettingsBuilder::new()
+        .with_temperature_offset_centi(-150)
+        .build();
+    dev.set_sensor_settings(&mut delay, settings).unwrap();
+    let (integer32, _) = dev.get_sensor_data(&mut delay).unwrap();
+    assert_eq!(format!("{:?}", integer32), format!("{:?}", integer));
+    dev.set_compensation(Compensation::Integer);
+    let (converted, _) = dev.get_sensor_data(&mut delay).unwrap();
+    assert_eq!(format!("{:?}", converted), format!("{:?}", integer));
+}
+
+#[test]
+fn integer32_compensation_truncates_the_temperature_offset() {
+    use bme680::{Compensation, SettingsBuilder};
+
+    let mut delay = NoDelay;
+    let mut i2c = RecordingI2c::new();
+    set_field_adc(&mut i2c.registers, 500000, 360000, 27000, 700, 7);
+    let (mut dev, _) = init_recording(i2c, &mut delay);
+
+    // Offsets between two hundredths of a degree, 1.006 would be rounded up to 1.01
+    for offset in [1.005, 1.006, -1.006, 0.999, -0.015] {
+        let settings = SettingsBuilder::new()
+            .with_temperature_offset(offset)
+            .build();
+        dev.set_sensor_settings(&mut delay, settings).unwrap();
+        dev.set_compensation(Compensation::Integer);
+        let (integer, _) = dev.get_sensor_data(&mut delay).unwrap();
+        dev.set_compensation(Compensation::Integer32);
+        let (integer32, _) = dev.get_sensor_data(&mut delay).unwrap();
+        assert_eq!(
+            format!("{:?}", integer32),
+            format!("{:?}", integer),
+            "{}",
+            offset
+        );
+    }
+}
+
+#[test]
+fn corrupted_field_data_fails_compensation() {
+    use bme680::{Compensation, CompensationError, Error};
+
+    let mut delay = NoDelay;
+    let mut i2c = RecordingI2c::new();
+    // All bits set, as read from a bus stuck high
+    set_field_adc(&mut i2c.registers, 500000, 0xfffff, 27000, 700, 7);
+    let (mut dev, _) = init_recording(i2c, &mut delay);
+
+    for compensation in [
+        Compensation::Integer,
+        Compensation::Integer32,
+        Compensation::Float,
+    ]
+    .iter()
+    {
+        dev.set_compensation(*compensation);
+        let result = dev.get_sensor_data(&mut delay);
+        assert!(
+            matches!(
+                result,
+                Err(Error::Compensation(CompensationError::Overflow("pressure")))
+            ),
+            "{:?}: {:?}",
+            compensation,
+            result
+        );
+    }
+    // The raw values are still available
+    let (raw, _) = dev.get_raw_sensor_data(&mut delay).unwrap();
+    assert_eq!(raw.adc_pres, 0xfffff);
+}
+
+#[test]
+fn init_with_calib_skips_calibration_readout() {
+    use bme680::CalibData;
+
+    let mut delay = NoDelay;
+    let mut i2c = RecordingI2c::new();
+    set_field_adc(&mut i2c.registers, 500000, 360000, 27000, 700, 7);
+    let log = i2c.log.clone();
+    let (data, stored) = {
+        let mut dev = Bme680::init_borrowed(&mut i2c, &mut delay, I2CAddress::Primary).unwrap();
+        let (data, _) = dev.get_sensor_data(&mut delay).unwrap();
+        (data, dev.calib_data().to_bytes())
+    };
+    log.borrow_mut().clear();
+
+    let calib = CalibData::from_bytes(&stored).unwrap();
+    let mut dev = Bme680::init_with_calib(i2c, &mut delay, I2CAddress::Primary, calib).unwrap();
+
+    assert_eq!(
+        *log.borrow(),
+        vec![
+            Transaction::Write {
+                addr: 0x76,
+                bytes: vec![0xe0, 0xb6],
+            },
+            write_read(0xd0, 1),
+            write_read(0xf0, 1),
+        ]
+    );
+    assert_eq!(dev.calib_data(), calib);
+    let (restored, _) = dev.get_sensor_data(&mut delay).unwrap();
+    assert_eq!(format!("{:?}", restored), format!("{:?}", data));
+}
+
+#[test]
+fn calib_data_round_trips_through_bytes() {
+    use bme680::{CalibData, CalibDataError, BME680_CALIB_DATA_LEN};
+
+    let mut delay = NoDelay;
+    let i2c = RecordingI2c::new();
+    let (dev, _) = init_recording(i2c, &mut delay);
+    let calib = dev.calib_data();
+
+    let bytes = calib.to_bytes();
+    assert_eq!(bytes.len(), BME680_CALIB_DATA_LEN);
+    assert_eq!(bytes[0], 1);
+    assert_eq!(CalibData::from_bytes(&bytes), Ok(calib));
+
+    // Every parameter is stored, extreme values included
+    let extreme = CalibData {
+        par_h1: u16::MAX,
+        par_h7: i8::MIN,
+        par_gh2: i16::MIN,
+        par_t2: i16::MAX,
+        par_p9: -1,
+        par_p10: u8::MAX,
+        range_sw_err: 15,
+        ..calib
+    };
+    assert_eq!(CalibData::from_bytes(&extreme.to_bytes()), Ok(extreme));
+
+    assert_eq!(
+        CalibData::from_bytes(&bytes[..BME680_CALIB_DATA_LEN - 1]),
+        Err(CalibDataError::InvalidLength)
+    );
+    let mut future = bytes;
+    future[0] = 2;
+    assert_eq!(
+        CalibData::from_bytes(&future),
+        Err(CalibDataError::UnsupportedVersion(2))
+    );
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn calib_data_round_trips_through_serde() {
+    use bme680::CalibData;
+
+    let mut delay = NoDelay;
+    let i2c = RecordingI2c::new();
+    let (dev, _) = init_recording(i2c, &mut delay);
+    let calib = dev.calib_data();
+
+    let json = serde_json::to_string(&calib).unwrap();
+    assert!(json.contains("\"par_t1\":26180"), "{}", json);
+    assert_eq!(serde_json::from_str::<CalibData>(&json).unwrap(), calib);
+}
+
+/// Returns `value` in `corrupt` for the first `corrupt_reads` reads of these registers
+struct FlakyCalibI2c {
+    inner: RecordingI2c,
+    corrupt: Range<usize>,
+    value: u8,
+    corrupt_reads: usize,
+}
+
+impl Write for FlakyCalibI2c {
+    type Error = ();
+
+    fn write(&mut self, addr: u8, bytes: &[u8]) -> Result<(), Self::Error> {
+        self.inner.write(addr, bytes)
+    }
+}
+
+impl WriteRead for FlakyCalibI2c {
+    type Error = ();
+
+    fn write_read(&mut self, addr: u8, bytes: &[u8], buffer: &mut [u8]) -> Result<(), Self::Error> {
+        self.inner.write_read(addr, bytes, buffer)?;
+        let start = bytes[0] as usize;
+        let read = start..start + buffer.len();
+        if read.contains(&self.corrupt.start) && self.corrupt_reads > 0 {
+            self.corrupt_reads -= 1;
+            for reg_addr in self.corrupt.clone() {
+                buffer[reg_addr - start] = self.value;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[test]
+fn implausible_calibration_is_read_again() {
+    use bme680::{I2cInterface, BME680_CALIB_READ_ATTEMPTS};
+
+    let mut delay = CountingDelay::default();
+    let inner = RecordingI2c::new();
+    let log = inner.log.clone();
+    let i2c = FlakyCalibI2c {
+        inner,
+        corrupt: 0x89..0xa1,
+        value: 0xff,
+        corrupt_reads: BME680_CALIB_READ_ATTEMPTS as usize - 1,
+    };
+    let dev = Bme680::init(i2c, &mut delay, I2CAddress::Primary).unwrap();
+    assert_eq!(dev.calib_data().par_t2, 26257);
+    assert_eq!(
+        count(&log, &write_read(0x89, 24)),
+        BME680_CALIB_READ_ATTEMPTS as usize
+    );
+
+    // Fails once all attempts returned implausible data
+    let inner = RecordingI2c::new();
+    let log = inner.log.clone();
+    let i2c = FlakyCalibI2c {
+        inner,
+        corrupt: 0x89..0xa1,
+        value: 0xff,
+        corrupt_reads: 5,
+    };
+    let interface = I2cInterface::new(i2c, I2CAddress::Primary);
+    let result = Bme680::init_with_calib_attempts(interface, &mut delay, 5);
+    assert!(
+        matches!(result, Err(bme680::Error::InvalidCalibration("coeff_0x89"))),
+        "{:?}",
+        result.err()
+    );
+    assert_eq!(count(&log, &write_read(0x89, 24)), 5);
+}
+
+#[test]
+fn blank_calibration_blocks_are_read_again() {
+    use bme680::BME680_CALIB_READ_ATTEMPTS;
+
+    let blocks = [
+        (0x89..0xa1, 0x00, "coeff_0x89"),
+        (0xe1..0xf0, 0x00, "coeff_0xe1"),
+        (0xe1..0xf0, 0xff, "coeff_0xe1"),
+        // A zero par_p1 would divide by zero
+        (0x8e..0x90, 0x00, "par_p1"),
+    ];
+    for (corrupt, value, name) in blocks {
+        let mut delay = NoDelay;
+        let inner = RecordingI2c::new();
+        let log = inner.log.clone();
+        let i2c = FlakyCalibI2c {
+            inner,
+            corrupt: corrupt.clone(),
+            value,
+            corrupt_reads: BME680_CALIB_READ_ATTEMPTS as usize - 1,
+        };
+        let dev = Bme680::init(i2c, &mut delay, I2CAddress::Primary).unwrap();
+        assert_eq!(dev.calib_data().validate(), Ok(()), "{}", name);
+        assert_eq!(
+            count(&log, &write_read(0x89, 24)),
+            BME680_CALIB_READ_ATTEMPTS as usize,
+            "{}",
+            name
+        );
+
+        let i2c = FlakyCalibI2c {
+            inner: RecordingI2c::new(),
+            corrupt,
+            value,
+            corrupt_reads: BME680_CALIB_READ_ATTEMPTS as usize,
+        };
+        let result = Bme680::init(i2c, &mut delay, I2CAddress::Primary);
+        assert!(
+            matches!(result, Err(bme680::Error::InvalidCalibration(parameter)) if parameter == name),
+            "{:?}",
+            result.err()
+        );
+    }
+}
+
+#[test]
+fn single_unusual_coefficients_are_accepted() {
+    // Parameters of other sensors may differ widely from the fixture, only blank blocks and
+    // zero divisors are rejected
+    let inner = RecordingI2c::new();
+    let log = inner.log.clone();
+    let i2c = FlakyCalibI2c {
+        inner,
+        corrupt: 0xed..0xee,
+        value: 0xff,
+        corrupt_reads: 1,
+    };
+    let dev = Bme680::init(i2c, &mut NoDelay, I2CAddress::Primary).unwrap();
+    assert_eq!(dev.calib_data().par_gh1, -1);
+    assert_eq!(count(&log, &write_read(0x89, 24)), 1);
+}
+
+#[test]
+fn implausible_calibration_is_rejected() {
+    use bme680::{CalibData, Error};
+
+    assert_eq!(CalibData::default().validate(), Err("coeff_0x89"));
+    let all_set = CalibData::from_bytes(&[[1].as_ref(), &[0xff; 37]].concat()).unwrap();
+    assert_eq!(all_set.validate(), Err("coeff_0x89"));
+
+    let mut delay = NoDelay;
+    let mut i2c = RecordingI2c::new();
+    let calib = Bme680::init_borrowed(&mut i2c, &mut delay, I2CAddress::Primary)
+        .unwrap()
+        .calib_data();
+    assert_eq!(calib.validate(), Ok(()));
+
+    assert_eq!(CalibData { par_t1: 0, ..calib }.validate(), Err("par_t1"));
+    assert_eq!(CalibData { par_p1: 0, ..calib }.validate(), Err("par_p1"));
+    // The humidity and gas block as read from a bus returning 0xFF, with 12 bit par_h1/par_h2
+    let humidity_blank = CalibData {
+        par_h1: 0xfff,
+        par_h2: 0xfff,
+        par_h3: -1,
+        par_h4: -1,
+        par_h5: -1,
+        par_h6: 0xff,
+        par_h7: -1,
+        par_gh1: -1,
+        par_gh2: -1,
+        par_gh3: -1,
+        par_t1: 0xffff,
+        ..calib
+    };
+    assert_eq!(humidity_blank.validate(), Err("coeff_0xe1"));
+    // Any single parameter may take any other value
+    assert_eq!(
+        CalibData {
+            par_h1: 0xfff,
+            par_gh2: 0,
+            res_heat_range: 4,
+            ..calib
+        }
+        .validate(),
+        Ok(())
+    );
+
+    // Stored calibration data is checked as well
+    let pressure_zeroed = CalibData { par_p1: 0, ..calib };
+    let result = Bme680::init_with_calib(i2c, &mut delay, I2CAddress::Primary, pressure_zeroed);
+    assert!(
+        matches!(result, Err(Error::InvalidCalibration("par_p1"))),
+        "{:?}",
+        result.err()
+    );
+}
+
+#[test]
+fn resume_measures_without_reset() {
+    use bme680::{OversamplingSetting, SettingsBuilder};
+
+    let mut delay = NoDelay;
+    let mut i2c = RecordingI2c::new();
+    set_field_adc(&mut i2c.registers, 500000, 360000, 27000, 700, 7);
+    let log = i2c.log.clone();
+    let (data, snapshot) = {
+        let mut dev = Bme680::init_borrowed(&mut i2c, &mut delay, I2CAddress::Primary).unwrap();
+        let settings = SettingsBuilder::new()
+            .with_temperature_oversampling(OversamplingSetting::OS8x)
+            .with_temperature_offset(-2.0)
+            .build();
+        dev.set_sensor_settings(&mut delay, settings).unwrap();
+        let (data, _) = dev.get_sensor_data(&mut delay).unwrap();
+        (data, dev.snapshot())
+    };
+    log.borrow_mut().clear();
+
+    let mut dev = Bme680::resume(i2c, &snapshot).unwrap();
+    assert!(log.borrow().is_empty());
+
+    let (resumed, _) = dev.get_sensor_data(&mut delay).unwrap();
+    assert_eq!(*log.borrow(), vec![write_read(0x1d, 15)]);
+    assert_eq!(format!("{:?}", resumed), format!("{:?}", data));
+    assert_eq!(format!("{:?}", dev.snapshot()), format!("{:?}", snapshot));
+}
+
+/// Sensor initialized at the primary address with a heater profile, in forced mode
+fn configured_snapshot(i2c: &mut RecordingI2c, delay: &mut NoDelay) -> bme680::Snapshot {
+    use bme680::{HeaterStep, SettingsBuilder};
+    use core::time::Duration;
+
+    let mut dev = Bme680::init_borrowed(i2c, delay, I2CAddress::Primary).unwrap();
+    let settings = SettingsBuilder::new()
+        .with_humidity_control(0x08)
+        .with_gas_measurement(Duration::from_millis(150), 320, 25)
+        .with_run_gas(true)
+        .build();
+    dev.set_sensor_settings(delay, settings).unwrap();
+    let steps: Vec<_> = (0..10)
+        .map(|step| HeaterStep::new(200 + step * 20, Duration::from_millis(100)))
+        .collect();
+    dev.set_heater_profile(delay, 25, &steps).unwrap();
+    dev.select_heater_step(delay, 7).unwrap();
+    dev.set_sensor_mode(delay, PowerMode::ForcedMode).unwrap();
+    dev.snapshot()
+}
+
+#[test]
+fn resume_restores_power_mode_and_gas_settings() {
+    use core::time::Duration;
+
+    let mut delay = NoDelay;
+    let mut i2c = RecordingI2c::new();
+    let snapshot = configured_snapshot(&mut i2c, &mut delay);
+
+    let dev = Bme680::<_, NoDelay>::resume(i2c, &snapshot).unwrap();
+    let resumed = dev.snapshot();
+    assert_eq!(format!("{:?}", resumed), format!("{:?}", snapshot));
+    assert_eq!(resumed.power_mode, PowerMode::ForcedMode);
+    let gas_sett = resumed.gas_sett;
+    assert_eq!(gas_sett.nb_conv, 7);
+    assert_eq!(gas_sett.heatr_ctrl, Some(0x08));
+    assert!(gas_sett.run_gas_measurement);
+    // The selected set-point of the heater profile replaced the gas measurement settings
+    assert_eq!(gas_sett.heatr_temp, Some(340));
+    assert_eq!(gas_sett.heatr_dur, Some(Duration::from_millis(100)));
+    assert_eq!(gas_sett.heater_profile[0].temperature, 200);
+    assert_eq!(gas_sett.heater_profile[9].temperature, 380);
+}
+
+/// Register access without an I²C address, like SPI
+struct Registers(RecordingI2c);
+
+impl bme680::Interface for Registers {
+    type ReadError = ();
+    type WriteError = ();
+
+    fn read_registers(&mut self, reg_addr: u8, buf: &mut [u8]) -> bme680::Result<(), (), ()> {
+        self.0
+            .write_read(0x76, &[reg_addr], buf)
+            .map_err(bme680::Error::I2CRead)
+    }
+
+    fn write_registers(&mut self, reg: &[(u8, u8)]) -> bme680::Result<(), (), ()> {
+        for (reg_addr, reg_data) in reg {
+            self.0
+                .write(0x76, &[*reg_addr, *reg_data])
+                .map_err(bme680::Error::I2CWrite)?;
+        }
+        Ok(())
+    }
+}
+
+#[test]
+fn resume_with_interface_measures_without_reset() {
+    let mut delay = NoDelay;
+    let i2c = RecordingI2c::new();
+    let log = i2c.log.clone();
+    let mut dev = Bme680::init_with_interface(Registers(i2c), &mut delay).unwrap();
+    dev.set_sensor_mode(&mut delay, PowerMode::ForcedMode)
+        .unwrap();
+    let (data, _) = dev.get_sensor_data(&mut delay).unwrap();
+    let snapshot = dev.snapshot();
+    assert!(snapshot.address.is_none());
+    let registers = dev.release_interface(&mut delay).unwrap();
+    log.borrow_mut().clear();
+
+    // Without an address the snapshot cannot be resumed on an I²C bus
+    let result = Bme680::<_, NoDelay>::resume(RecordingI2c::new(), &snapshot);
+    assert!(matches!(result, Err(bme680::SnapshotError::MissingAddress)));
+
+    let mut dev = Bme680::resume_with_interface(registers, &snapshot);
+    assert!(log.borrow().is_empty());
+    dev.set_sensor_mode(&mut delay, PowerMode::ForcedMode)
+        .unwrap();
+    let (resumed, _) = dev.get_sensor_data(&mut delay).unwrap();
+    assert_eq!(format!("{:?}", resumed), format!("{:?}", data));
+    assert_eq!(dev.snapshot().power_mode, PowerMode::ForcedMode);
+}
+
+#[test]
+fn snapshot_round_trips_through_bytes() {
+    use bme680::{Snapshot, SnapshotError, BME680_SNAPSHOT_LEN};
+
+    let mut delay = NoDelay;
+    let mut i2c = RecordingI2c::new();
+    let snapshot = configured_snapshot(&mut i2c, &mut delay);
+
+    let bytes = snapshot.to_bytes();
+    assert_eq!(bytes.len(), BME680_SNAPSHOT_LEN);
+    assert_eq!(bytes[0], 1);
+    let restored = Snapshot::from_bytes(&bytes).unwrap();
+    assert_eq!(format!("{:?}", restored), format!("{:?}", snapshot));
+
+    for address in [
+        None,
+        Some(I2CAddress::Secondary),
+        Some(I2CAddress::Other(0x42)),
+    ]
+    .iter()
+    {
+        let snapshot = Snapshot {
+            address: *address,
+            ..snapshot
+        };
+        let restored = Snapshot::from_bytes(&snapshot.to_bytes()).unwrap();
+        assert_eq!(format!("{:?}", restored), format!("{:?}", snapshot));
+    }
+
+    assert!(matches!(
+        Snapshot::from_bytes(&bytes[..BME680_SNAPSHOT_LEN - 1]),
+        Err(SnapshotError::InvalidLength)
+    ));
+    let mut future = bytes;
+    future[0] = 2;
+    assert!(matches!(
+        Snapshot::from_bytes(&future),
+        Err(SnapshotError::UnsupportedVersion(2))
+    ));
+    // Power mode follows the version, address, variant, calibration data and compensation
+    let mut unknown = bytes;
+    unknown[43] = 4;
+    assert!(matches!(
+        Snapshot::from_bytes(&unknown),
+        Err(SnapshotError::InvalidValue("power_mode"))
+    ));
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn snapshot_round_trips_through_serde() {
+    use bme680::Snapshot;
+
+    let mut delay = NoDelay;
+    let mut i2c = RecordingI2c::new();
+    let snapshot = configured_snapshot(&mut i2c, &mut delay);
+
+    let json = serde_json::to_string(&snapshot).unwrap();
+    assert!(json.contains("\"power_mode\":\"ForcedMode\""), "{}", json);
+    let restored = serde_json::from_str::<Snapshot>(&json).unwrap();
+    assert_eq!(format!("{:?}", restored), format!("{:?}", snapshot));
+}
+
+#[cfg(feature = "async")]
+mod asynch {
+    use super::*;
+    use bme680::{Bme680Async, OversamplingSetting, SettingsBuilder};
+    use core::future::Future;
+    use core::pin::pin;
+    use core::task::{Context, Poll, RawWaker, RawWakerVTable, Waker};
+    use core::time::Duration;
+    use embedded_hal_async::delay::DelayNs;
+    use embedded_hal_async::i2c::{ErrorKind, ErrorType, I2c, Operation};
+
+    impl ErrorType for RecordingI2c {
+        type Error = ErrorKind;
+    }
+
+    impl I2c for RecordingI2c {
+        async fn transaction(
+            &mut self,
+            addr: u8,
+            operations: &mut [Operation<'_>],
+        ) -> Result<(), Self::Error> {
+            match operations {
+                [Operation::Write(bytes)] => Write::write(self, addr, bytes),
+                [Operation::Write(bytes), Operation::Read(buffer)] => {
+                    WriteRead::write_read(self, addr, bytes, buffer)
+                }
+                _ => Err(()),
+            }
+            .map_err(|_| ErrorKind::Other)
+        }
+    }
+
+    impl DelayNs for NoDelay {
+        async fn delay_ns(&mut self, _ns: u32) {}
+    }
+
+    /// Polls the future until it completes, the driver never waits on anything but the bus
+    fn block_on<F: Future>(future: F) -> F::Output {
+        fn noop_raw_waker() -> RawWaker {
+            fn clone(_: *const ()) -> RawWaker {
+                noop_raw_waker()
+            }
+            fn noop(_: *const ()) {}
+            static VTABLE: RawWakerVTable = RawWakerVTable::new(clone, noop, noop, noop);
+            RawWaker::new(core::ptr::null(), &VTABLE)
+        }
+
+        let waker = unsafe { Waker::from_raw(noop_raw_waker()) };
+        let mut context = Context::from_waker(&waker);
+        let mut future = pin!(future);
+        loop {
+            if let Poll::Ready(output) = future.as_mut().poll(&mut context) {
+                return output;
+            }
+        }
+    }
+
+    fn settings() -> bme680::Settings {
+        SettingsBuilder::new()
+            .with_humidity_oversampling(OversamplingSetting::OS2x)
+            .with_pressure_oversampling(OversamplingSetting::OS4x)
+            .with_temperature_oversampling(OversamplingSetting::OS8x)
+            .with_gas_measurement(Duration::from_millis(1500), 320, 25)
+            .with_run_gas(true)
+            .build()
+    }
+
+    #[test]
+    fn init_issues_the_transactions_of_the_blocking_driver() {
+        let i2c = RecordingI2c::new();
+        let log = i2c.log.clone();
+        block_on(Bme680Async::init(i2c, &mut NoDelay, I2CAddress::Primary)).unwrap();
+
+        let (_, blocking_log) = init_recording(RecordingI2c::new(), &mut NoDelay);
+        assert_eq!(*log.borrow(), *blocking_log.borrow());
+        assert_eq!(
+            *log.borrow(),
+            vec![
+                Transaction::Write {
+                    addr: 0x76,
+                    bytes: vec![0xe0, 0xb6],
+                },
+                write_read(0xd0, 1),
+                write_read(0xf0, 1),
+                write_read(0x89, 24),
+                write_read(0xe1, 15),
+                write_read(0x02, 1),
+                write_read(0x00, 1),
+                write_read(0x04, 1),
+            ]
+        );
+    }
+
+    #[test]
+    fn measurement_issues_the_transactions_of_the_blocking_driver() {
+        let i2c = RecordingI2c::new();
+        let log = i2c.log.clone();
+        let data = block_on(async {
+            let mut dev = Bme680Async::init(i2c, &mut NoDelay, I2CAddress::Primary).await?;
+            log.borrow_mut().clear();
+            dev.set_sensor_settings(&mut NoDelay, settings()).await?;
+            dev.set_sensor_mode(&mut NoDelay, PowerMode::ForcedMode)
+                .await?;
+            dev.get_sensor_data(&mut NoDelay).await
+        })
+        .unwrap()
+        .0;
+
+        let (mut dev, blocking_log) = init_recording(RecordingI2c::new(), &mut NoDelay);
+        blocking_log.borrow_mut().clear();
+        dev.set_sensor_settings(&mut NoDelay, settings()).unwrap();
+        dev.set_sensor_mode(&mut NoDelay, PowerMode::ForcedMode)
+            .unwrap();
+        let (blocking_data, _) = dev.get_sensor_data(&mut NoDelay).unwrap();
+
+        assert_eq!(*log.borrow(), *blocking_log.borrow());
+        // The settings are written in a single burst and the field in a single read
+        let writes = writes(&log);
+        assert_eq!(writes.len(), 2);
+        let registers: Vec<u8> = writes[0].chunks(2).map(|pair| pair[0]).collect();
+        assert_eq!(registers, [0x5a, 0x64, 0x74, 0x72, 0x71]);
+        assert_eq!(count(&log, &write_read(0x1d, 15)), 1);
+        assert_eq!(format!("{:?}", data), format!("{:?}", blocking_data));
+    }
+}